@@ -1,5 +1,7 @@
 extern crate nannou;
 use nannou::prelude::*;
+use params::{Panel, Params};
+use rand::{rngs::StdRng, Rng, SeedableRng};
 
 fn main() {
     nannou::app(model).update(update).run();
@@ -8,6 +10,17 @@ fn main() {
 struct Model {
     _window: window::Id,
     points: Vec<Vector2>,
+    panel: Panel<Model>,
+}
+
+impl Model {
+    fn scatter(&mut self) {
+        let count = self.panel.params.usize("points");
+        let mut rng = StdRng::seed_from_u64(self.panel.params.seed());
+        self.points = (0..count)
+            .map(|_x| Vector2::from((rng.gen_range(-512., 512.), rng.gen_range(-400., 400.))))
+            .collect::<Vec<_>>();
+    }
 }
 
 fn model(app: &App) -> Model {
@@ -19,16 +32,30 @@ fn model(app: &App) -> Model {
         .build()
         .unwrap();
 
-    let points = (0..500)
-        .map(|_x| Vector2::from((random_range(-512., 512.), random_range(-400., 400.))))
-        .collect::<Vec<_>>();
-    Model { _window, points }
+    let params = Params::new()
+        .int("points", 500, 10..=2000)
+        .float("range", 80., 10.0..=200.)
+        .float("md", 300., 10.0..=600.)
+        .args();
+    let panel = Panel::new(app, _window, params).on_change(&["points", "seed"], Model::scatter);
+    let mut model = Model {
+        _window,
+        points: Vec::new(),
+        panel,
+    };
+    model.scatter();
+    model
 }
 
-fn update(_app: &App, _model: &mut Model, _update: Update) {}
+fn update(_app: &App, model: &mut Model, _update: Update) {
+    Panel::update(model, |model| &mut model.panel);
+}
 
-fn window_event(_app: &App, _model: &mut Model, event: WindowEvent) {
+fn window_event(_app: &App, model: &mut Model, event: WindowEvent) {
     match event {
+        KeyPressed(Key::P) => model.panel.toggle(),
+        KeyPressed(Key::N) => model.panel.params.reseed(),
+        KeyPressed(Key::S) => model.panel.params.save("bounds.toml").unwrap(),
         KeyPressed(_key) => {}
         KeyReleased(_key) => {}
         MouseMoved(_pos) => {}
@@ -61,8 +88,8 @@ fn view(app: &App, model: &Model, frame: &Frame) {
         draw.ellipse().w_h(1., 1.).x_y(p.x, p.y);
     }
 
-    let range = 80.;
-    let md = 300.;
+    let range = model.panel.params.f32("range");
+    let md = model.panel.params.f32("md");
 
     let mx = app.mouse.x;
     let my = app.mouse.y;
@@ -83,4 +110,5 @@ fn view(app: &App, model: &Model, frame: &Frame) {
 
     // Write to the window frame.
     draw.to_frame(app, &frame).unwrap();
+    model.panel.draw(app, &frame);
 }
@@ -1,4 +1,7 @@
 use nannou::prelude::*;
+use painter::Painter;
+use params::{Panel, Params};
+use plotter::{GcodeOptions, HpglOptions, Recorder};
 
 fn main() {
     nannou::app(model).update(update).run();
@@ -7,6 +10,7 @@ fn main() {
 struct Model {
     _window: window::Id,
     counter: usize,
+    panel: Panel<Model>,
 }
 
 fn model(app: &App) -> Model {
@@ -17,18 +21,52 @@ fn model(app: &App) -> Model {
         .event(window_event)
         .build()
         .unwrap();
+    let params = Params::new()
+        .float("ratio", 0.05, 0.0..=0.5)
+        .int("iterations", 4, 0..=8)
+        .toggle("closed", false)
+        .args();
     Model {
         _window,
         counter: 0,
+        panel: Panel::new(app, _window, params),
     }
 }
 
 fn update(_app: &App, model: &mut Model, _update: Update) {
+    Panel::update(model, |model| &mut model.panel);
     model.counter += 1;
 }
 
-fn window_event(_app: &App, _model: &mut Model, event: WindowEvent) {
+fn window_event(app: &App, model: &mut Model, event: WindowEvent) {
     match event {
+        KeyPressed(Key::G) => {
+            let recorder = Recorder::new();
+            draw_curve(&recorder, model);
+            recorder
+                .save_gcode(
+                    "chaikin_curve.gcode",
+                    app.window_rect(),
+                    &GcodeOptions::default(),
+                )
+                .and_then(|_| model.panel.params.save_beside("chaikin_curve.gcode"))
+                .map(|_| ())
+                .unwrap_or_else(|err| eprintln!("{}", err));
+        }
+        KeyPressed(Key::H) => {
+            let recorder = Recorder::new();
+            draw_curve(&recorder, model);
+            recorder
+                .save_hpgl(
+                    "chaikin_curve.hpgl",
+                    app.window_rect(),
+                    &HpglOptions::default(),
+                )
+                .and_then(|_| model.panel.params.save_beside("chaikin_curve.hpgl"))
+                .map(|_| ())
+                .unwrap_or_else(|err| eprintln!("{}", err));
+        }
+        KeyPressed(Key::P) => model.panel.toggle(),
         KeyPressed(_key) => {}
         KeyReleased(_key) => {}
         MouseMoved(_pos) => {}
@@ -56,6 +94,13 @@ fn view(app: &App, model: &Model, frame: &Frame) {
     // Clear the background to pink.
     draw.background().color(PLUM);
     // Draw a red ellipse with default size and position.
+    draw_curve(&draw, model);
+    // Write to the window frame.
+    draw.to_frame(app, &frame).unwrap();
+    model.panel.draw(app, &frame);
+}
+
+fn draw_curve<P: Painter>(painter: &P, model: &Model) {
     let points = vec![
         pt2(-150., -150.),
         pt2(-50., 50.),
@@ -66,13 +111,18 @@ fn view(app: &App, model: &Model, frame: &Frame) {
         pt2(150., 150.),
         pt2(200., -200.),
     ];
-    draw.polyline().weight(5.).points(
-        chaikin_open(points, 0.05, model.counter / 50)
-            .iter()
-            .map(|&x| x + pt2(0., 0.)),
-    );
-    // Write to the window frame.
-    draw.to_frame(app, &frame).unwrap();
+    let params = &model.panel.params;
+    // The curve refines itself over time up to `iterations` cuts.
+    let iterations = (model.counter / 50).min(params.usize("iterations"));
+    let ratio = params.f32("ratio");
+    if params.bool("closed") {
+        let mut curve = chaikin_close(points, ratio, iterations);
+        curve.push(curve[0]);
+        painter.polyline(&curve, 5., rgba(1., 1., 1., 1.));
+    } else {
+        let curve = chaikin_open(points, ratio, iterations);
+        painter.polyline(&curve, 5., rgba(1., 1., 1., 1.));
+    }
 }
 
 fn chaikin_cut(a: Point2, b: Point2, mut ratio: f32) -> Vec<Point2> {
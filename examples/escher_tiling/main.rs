@@ -1,4 +1,6 @@
+use capture::{Clock, FrameWriter};
 use nannou::prelude::*;
+use painter::{Painter, Raster};
 
 use std::time::Duration;
 
@@ -8,55 +10,86 @@ use screenshot::Shots;
 
 struct Model {
     screenshot: Shots,
-    capture: bool,
-    counter: usize,
+    clock: Clock,
+    time: f32,
 }
 
 extern crate num_complex;
 
+const WIDTH: u32 = 1024;
+const HEIGHT: u32 = 768;
+
 fn main() {
+    let clock = Clock::from_args();
+    if clock.is_headless() {
+        headless(clock);
+        return;
+    }
     nannou::app(model).update(update).exit(exit).run();
 }
 
+/// Every frame of the clock drawn on the CPU into `frames/`, no window.
+fn headless(clock: Clock) {
+    let mut writer = FrameWriter::new("frames", 16).expect("can't create frames directory");
+    capture::render(clock, &mut writer, |t| {
+        let raster = Raster::new(WIDTH, HEIGHT);
+        draw_tiling(&raster, Rect::from_w_h(WIDTH as f32, HEIGHT as f32), t);
+        raster.into_image()
+    });
+    writer.flush(Duration::from_secs(60));
+}
+
 fn model(app: &App) -> Model {
     let window_id = app
         .new_window()
-        .with_dimensions(1024, 768)
+        .with_dimensions(WIDTH, HEIGHT)
+        .msaa_samples(1)
         .view(view)
         .event(window_event)
         .build()
         .unwrap();
     let screenshot = screenshot::new(app, window_id);
+    // Offline every frame is taken in update, otherwise record every
+    // second frame from the start, R toggles it
+    let clock = Clock::from_args();
+    if !clock.is_offline() {
+        screenshot.every(Some(2));
+    }
     Model {
         screenshot,
-        capture: true,
-        counter: 0,
+        clock,
+        time: 0.,
     }
 }
 
 fn update(app: &App, model: &mut Model, _update: Update) {
-    model.counter += 1;
+    if model.clock.finished() {
+        app.quit();
+        return;
+    }
+    model.time = model.clock.tick(app.time);
+    if model.clock.is_offline() {
+        model.screenshot.take();
+    }
 }
 
 fn view(app: &App, model: &Model, frame: &Frame) {
     let draw = app.draw();
-    let t = app.time;
+    draw_tiling(&draw, app.window_rect(), model.time);
+    draw.to_frame(app, &frame).unwrap();
 
-    draw.background().color(BLACK);
+    model.screenshot.capture(&frame);
+}
 
-    let (w, h) = app.window_rect().w_h();
+fn draw_tiling<P: Painter>(painter: &P, win: Rect, t: f32) {
+    painter.background(srgba(0., 0., 0., 1.));
+
+    let (w, h) = win.w_h();
 
     let frac = 6.;
     let lines = quad_fill(0., 0., w / frac, h / frac, 3., 1. + t * 0.025);
     // Here applies complex function
-    mesh_from_arr(&lines, &draw, 0.05);
-
-    draw.to_frame(app, &frame).unwrap();
-
-    if model.capture && model.counter % 2 == 0 {
-        model.screenshot.capture(&frame);
-        model.screenshot.take();
-    }
+    mesh_from_arr(&lines, painter, 0.05);
 }
 
 pub fn encode_endpoint(x: f32, y: f32, clipx: f32, clipy: f32, clipw: f32, cliph: f32) -> usize {
@@ -172,7 +205,7 @@ pub fn quad_fill(x: f32, y: f32, width: f32, height: f32, step: f32, a: f32) ->
     let lenght = (width * width + height * height).sqrt();
     let num_steps = lenght / (2. * step);
 
-    let num_points = 1000;
+    let spacing = 0.25;
 
     let mut arr = Vec::new();
 
@@ -193,14 +226,10 @@ pub fn quad_fill(x: f32, y: f32, width: f32, height: f32, step: f32, a: f32) ->
         width,
         height,
     ) {
-        arr.push(
-            (0..num_points)
-                .map(|i| {
-                    pt2(start[0], start[1])
-                        .lerp(pt2(end[0], end[1]), i as f32 / (num_points - 1) as f32)
-                })
-                .collect::<Vec<_>>(),
-        )
+        arr.push(polyline::resample(
+            &[pt2(start[0], start[1]), pt2(end[0], end[1])],
+            spacing,
+        ))
     }
     for _ in 0..num_steps as usize {
         x0 += step * norm[0];
@@ -217,14 +246,10 @@ pub fn quad_fill(x: f32, y: f32, width: f32, height: f32, step: f32, a: f32) ->
             width,
             height,
         ) {
-            arr.push(
-                (0..num_points)
-                    .map(|i| {
-                        pt2(start[0], start[1])
-                            .lerp(pt2(end[0], end[1]), i as f32 / (num_points - 1) as f32)
-                    })
-                    .collect::<Vec<_>>(),
-            )
+            arr.push(polyline::resample(
+                &[pt2(start[0], start[1]), pt2(end[0], end[1])],
+                spacing,
+            ))
         }
         if let Some((start, end)) = line_clipped(
             -x0 + 2. * x,
@@ -236,21 +261,17 @@ pub fn quad_fill(x: f32, y: f32, width: f32, height: f32, step: f32, a: f32) ->
             width,
             height,
         ) {
-            arr.push(
-                (0..num_points)
-                    .map(|i| {
-                        pt2(start[0], start[1])
-                            .lerp(pt2(end[0], end[1]), i as f32 / (num_points - 1) as f32)
-                    })
-                    .collect::<Vec<_>>(),
-            )
+            arr.push(polyline::resample(
+                &[pt2(start[0], start[1]), pt2(end[0], end[1])],
+                spacing,
+            ))
         }
     }
 
     arr
 }
 
-fn mesh_from_arr(arr: &[Vec<Vector2>], draw: &nannou::app::Draw, weight: f32) {
+fn mesh_from_arr<P: Painter>(arr: &[Vec<Vector2>], painter: &P, weight: f32) {
     let cfunc = |points: [Vector2; 4]| {
         let mut res = [pt2(0., 0.); 4];
         for (i, p) in points.iter().enumerate() {
@@ -263,7 +284,7 @@ fn mesh_from_arr(arr: &[Vec<Vector2>], draw: &nannou::app::Draw, weight: f32) {
 
     for line in arr {
         let num_points = line.len();
-        let tris = line
+        let vertices: Vec<(Point2, Srgba)> = line
             .windows(2)
             .flat_map(|slice| {
                 let dev = (slice[1] - slice[0]).angle() + TAU / 4.;
@@ -273,20 +294,20 @@ fn mesh_from_arr(arr: &[Vec<Vector2>], draw: &nannou::app::Draw, weight: f32) {
                 let c = slice[1] + dev;
                 let d = slice[1] - dev;
                 let [a, b, c, d] = cfunc([a, b, c, d]);
-                geom::Quad([a, c, d, b]).triangles_iter()
+                // The two triangles of the quad a, c, d, b.
+                vec![[a, c, d], [a, d, b]]
             })
             .enumerate()
-            .map(|(i, tri)| {
+            .flat_map(|(i, tri)| {
                 let i = i as f32 / num_points as f32;
-                let mut j = 0.;
-                tri.map_vertices(|v| {
-                    let color = srgba(map_range(j, 0., 3., 0., 1.), i, 1. - i, 1.0);
-                    j += 1.;
-                    geom::vertex::Srgba(v, color)
+                (0..3).map(move |j| {
+                    let color = srgba(map_range(j as f32, 0., 3., 0., 1.), i, 1. - i, 1.0);
+                    (tri[j], color)
                 })
-            });
+            })
+            .collect();
 
-        draw.mesh().tris(tris);
+        painter.mesh(&vertices);
     }
 }
 
@@ -297,6 +318,15 @@ fn window_event(_app: &App, model: &mut Model, event: WindowEvent) {
                 // Adds a screenshot to the queue to be taken
                 model.screenshot.take();
             }
+            if let Key::R = key {
+                // Toggles recording every second frame
+                let every = if model.screenshot.is_recording() {
+                    None
+                } else {
+                    Some(2)
+                };
+                model.screenshot.every(every);
+            }
         }
         KeyReleased(_key) => {}
         MouseMoved(_pos) => {}
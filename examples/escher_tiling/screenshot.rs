@@ -0,0 +1,144 @@
+//! Frame capture for a window, frames end up as numbered PNGs.
+//!
+//! The copy out of the frame runs on the GPU after `view` returns, so each
+//! capture is read back on a later frame once the GPU has let go of the
+//! buffer, and encoded on the writer thread from there.
+use capture::FrameWriter;
+use nannou::prelude::*;
+use nannou::vk;
+use std::cell::{Cell, RefCell};
+use std::collections::VecDeque;
+use std::error::Error;
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Directory the frames are written to.
+const DIR: &str = "frames";
+/// Frames allowed to wait for the disk before capturing blocks.
+const QUEUE: usize = 16;
+
+/// Pixels of the frame as nannou renders them, linear 16 bit RGBA.
+type Pixels = vk::CpuAccessibleBuffer<[[u16; 4]]>;
+
+pub struct Shots {
+    device: Arc<vk::Device>,
+    writer: RefCell<FrameWriter>,
+    pending: Cell<usize>,
+    every: Cell<Option<usize>>,
+    frame: Cell<usize>,
+    downloads: RefCell<VecDeque<(Arc<Pixels>, [u32; 2])>>,
+}
+
+/// Capture frames of `window_id`. The window has to be built without
+/// MSAA, multisampled images can't be copied into a buffer.
+pub fn new(app: &App, window_id: window::Id) -> Shots {
+    let window = app.window(window_id).expect("no window to capture");
+    Shots {
+        device: window.swapchain_device().clone(),
+        writer: RefCell::new(FrameWriter::new(DIR, QUEUE).expect("can't create frames directory")),
+        pending: Cell::new(0),
+        every: Cell::new(None),
+        frame: Cell::new(0),
+        downloads: RefCell::new(VecDeque::new()),
+    }
+}
+
+impl Shots {
+    /// Capture the next frame passed to `capture`.
+    pub fn take(&self) {
+        self.pending.set(self.pending.get() + 1);
+    }
+
+    /// Capture every `n`th frame on top of the ones asked for with `take`,
+    /// `None` stops recording.
+    pub fn every(&self, n: Option<usize>) {
+        self.every.set(n.filter(|&n| n > 0));
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.every.get().is_some()
+    }
+
+    /// Call at the end of every `view`, after drawing to the frame.
+    pub fn capture(&self, frame: &Frame) {
+        self.save_finished();
+
+        let index = self.frame.get();
+        self.frame.set(index + 1);
+        let scheduled = self.every.get().map(|n| index % n == 0).unwrap_or(false);
+        if self.pending.get() == 0 && !scheduled {
+            return;
+        }
+        if self.pending.get() > 0 {
+            self.pending.set(self.pending.get() - 1);
+        }
+
+        match self.download(frame) {
+            Ok(download) => self.downloads.borrow_mut().push_back(download),
+            Err(err) => eprintln!("failed to capture frame: {}", err),
+        }
+    }
+
+    /// Wait up to `wait` for the GPU and the disk to finish, call on exit.
+    pub fn flush(self, wait: Duration) {
+        let deadline = Instant::now() + wait;
+        while !self.downloads.borrow().is_empty() && Instant::now() < deadline {
+            self.save_finished();
+            thread::sleep(Duration::from_millis(10));
+        }
+        let lost = self.downloads.borrow().len();
+        if lost > 0 {
+            eprintln!("{} frames were still on the GPU, raise the wait time", lost);
+        }
+        let writer = self.writer.into_inner();
+        let left = deadline.saturating_duration_since(Instant::now());
+        if !writer.flush(left) {
+            eprintln!("frames were still being written, raise the wait time");
+        }
+    }
+
+    /// Record a copy of the frame image into a buffer the CPU can read.
+    fn download(&self, frame: &Frame) -> Result<(Arc<Pixels>, [u32; 2]), Box<dyn Error>> {
+        let image = frame.image().clone();
+        let [width, height] = image.dimensions();
+        let buffer = vk::CpuAccessibleBuffer::from_iter(
+            self.device.clone(),
+            vk::BufferUsage::transfer_destination(),
+            (0..width * height).map(|_| [0u16; 4]),
+        )?;
+        frame
+            .add_commands()
+            .copy_image_to_buffer(image, buffer.clone())?;
+        Ok((buffer, [width, height]))
+    }
+
+    /// Hand every download the GPU is done with to the writer, in order.
+    fn save_finished(&self) {
+        let mut downloads = self.downloads.borrow_mut();
+        while let Some((buffer, [width, height])) = downloads.front() {
+            let pixels = match buffer.read() {
+                Ok(pixels) => pixels.to_vec(),
+                Err(_) => break,
+            };
+            let (width, height) = (*width, *height);
+            downloads.pop_front();
+            self.writer.borrow_mut().push_with(move || {
+                let mut image = image::RgbaImage::new(width, height);
+                for (dst, src) in image.pixels_mut().zip(&pixels) {
+                    let c = |v: u16| (to_srgb(v as f32 / 65535.) * 255.).round() as u8;
+                    *dst = image::Rgba([c(src[0]), c(src[1]), c(src[2]), (src[3] >> 8) as u8]);
+                }
+                image
+            });
+        }
+    }
+}
+
+fn to_srgb(linear: f32) -> f32 {
+    if linear <= 0.003_130_8 {
+        linear * 12.92
+    } else {
+        1.055 * linear.powf(1. / 2.4) - 0.055
+    }
+}
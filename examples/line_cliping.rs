@@ -1,38 +1,141 @@
-use nannou::noise::NoiseFn;
+use capture::{Clock, FrameWriter};
+use nannou::noise::{NoiseFn, Perlin, Seedable};
 use nannou::prelude::*;
+use painter::{Painter, Raster};
+use params::{cli, Params, Value};
+use plotter::{Options, Recorder, SvgOptions};
+use std::cell::RefCell;
+use std::path::Path;
+use std::time::Duration;
+
+const SIZE: u32 = 720;
+/// Noise offset per second of animation.
+const SPEED: f64 = 0.3;
 
 fn main() {
-    nannou::app(model).update(update).run();
+    if let Some(file) = cli::render_target() {
+        still(&file);
+        return;
+    }
+    let clock = Clock::from_args();
+    if clock.is_headless() {
+        headless(clock);
+        return;
+    }
+    nannou::app(model).update(update).exit(exit).run();
 }
 
 struct Model {
     _window: window::Id,
-    perlin: nannou::noise::Perlin,
+    params: Params,
+    perlin: Perlin,
+    clock: Clock,
     counter: f64,
+    // Offline frames are rendered on the CPU as well and written out.
+    frames: Option<RefCell<FrameWriter>>,
 }
 
 fn model(app: &App) -> Model {
     // app.set_loop_mode(LoopMode::wait(1));
+    let (w, h) = cli::size((SIZE, SIZE));
     let _window = app
         .new_window()
-        .with_dimensions(720, 720)
+        .with_dimensions(w, h)
         .view(view)
         .event(window_event)
         .build()
         .unwrap();
+    let clock = Clock::from_args();
+    let frames = if clock.is_offline() {
+        let writer = FrameWriter::new("frames", 16).expect("can't create frames directory");
+        Some(RefCell::new(writer))
+    } else {
+        None
+    };
+    let params = params();
     Model {
         _window,
-        perlin: nannou::noise::Perlin::new(),
+        perlin: Perlin::new().set_seed(params.seed() as u32),
+        params,
+        clock,
         counter: 0.,
+        frames,
+    }
+}
+
+fn update(app: &App, model: &mut Model, _update: Update) {
+    if model.clock.finished() {
+        app.quit();
+        return;
+    }
+    model.counter = model.clock.tick(app.time) as f64 * SPEED;
+}
+
+fn exit(_app: &App, model: Model) {
+    if let Some(frames) = model.frames {
+        frames.into_inner().flush(Duration::from_secs(60));
+    }
+}
+
+/// `time` is where a still is taken, it is kept up to date in saved presets.
+fn params() -> Params {
+    Params::new().float("time", 0., 0.0..=3600.).args()
+}
+
+/// Render the frames straight to disk without opening a window.
+fn headless(clock: Clock) {
+    let perlin = Perlin::new().set_seed(params().seed() as u32);
+    let mut writer = FrameWriter::new("frames", 16).expect("can't create frames directory");
+    capture::render(clock, &mut writer, |t| render(&perlin, t as f64 * SPEED));
+    writer.flush(Duration::from_secs(60));
+}
+
+/// Draw the still for the preset into `file`, SVG lines or PNG pixels by its
+/// extension.
+fn still(file: &Path) {
+    let params = params();
+    let perlin = Perlin::new().set_seed(params.seed() as u32);
+    let counter = params.f32("time") as f64 * SPEED;
+    let result = if file.extension().and_then(|ext| ext.to_str()) == Some("svg") {
+        let (w, h) = cli::size((SIZE, SIZE));
+        let area = Rect::from_w_h(w as f32, h as f32);
+        let recorder = Recorder::new();
+        draw_hatching(&recorder, area, &perlin, counter);
+        recorder.optimize(&Options::default());
+        recorder.save_svg(file, area, &SvgOptions::default())
+    } else {
+        render(&perlin, counter).save(file)
+    };
+    if let Err(err) = result {
+        eprintln!("failed to write {}: {}", file.display(), err);
+        std::process::exit(1);
     }
 }
 
-fn update(_app: &App, model: &mut Model, _update: Update) {
-    model.counter += 0.005;
+fn render(perlin: &Perlin, counter: f64) -> image::RgbaImage {
+    let (w, h) = cli::size((SIZE, SIZE));
+    let raster = Raster::new(w, h);
+    raster.background(rgba(0., 0., 0., 1.));
+    let area = Rect::from_w_h(w as f32, h as f32);
+    draw_hatching(&raster, area, perlin, counter);
+    raster.into_image()
 }
 
-fn window_event(_app: &App, _model: &mut Model, event: WindowEvent) {
+fn window_event(app: &App, model: &mut Model, event: WindowEvent) {
     match event {
+        KeyPressed(Key::S) => {
+            let recorder = Recorder::new();
+            let win = app.window_rect();
+            draw_hatching(&recorder, win, &model.perlin, model.counter);
+            let report = recorder.optimize(&Options::default());
+            println!("{}", report);
+            recorder
+                .save_svg("line_cliping.svg", win, &SvgOptions::default())
+                .unwrap();
+            let time = Value::Float((model.counter / SPEED) as f32);
+            model.params.set("time", time).unwrap();
+            model.params.save_beside("line_cliping.svg").unwrap();
+        }
         KeyPressed(_key) => {}
         KeyReleased(_key) => {}
         MouseMoved(_pos) => {}
@@ -61,7 +164,21 @@ fn view(app: &App, model: &Model, frame: &Frame) {
     draw.background().color(BLACK);
     // Draw a red ellipse with default size and position.
 
-    let win = app.window_rect();
+    draw_hatching(&draw, app.window_rect(), &model.perlin, model.counter);
+
+    // Once per tick, view can run more often than update.
+    if let Some(frames) = &model.frames {
+        let mut frames = frames.borrow_mut();
+        if (frames.frames() as u64) < model.clock.frame() {
+            frames.push(render(&model.perlin, model.counter));
+        }
+    }
+
+    // Write to the window frame.
+    draw.to_frame(app, &frame).unwrap();
+}
+
+fn draw_hatching<P: Painter>(painter: &P, win: Rect, perlin: &Perlin, counter: f64) {
     let frac = 50;
     let w = win.w() / frac as f32;
     let h = win.h() / frac as f32;
@@ -73,18 +190,11 @@ fn view(app: &App, model: &Model, frame: &Frame) {
                 yoff,
                 w,
                 7.,
-                model
-                    .perlin
-                    .get([xoff as f64 * 0.001, yoff as f64 * 0.001, model.counter])
-                    as f32
-                    * TAU,
-                &draw,
+                perlin.get([xoff as f64 * 0.001, yoff as f64 * 0.001, counter]) as f32 * TAU,
+                painter,
             );
         }
     }
-
-    // Write to the window frame.
-    draw.to_frame(app, &frame).unwrap();
 }
 
 fn encode_endpoint(x: f32, y: f32, clipx: f32, clipy: f32, clipw: f32, cliph: f32) -> usize {
@@ -110,7 +220,7 @@ fn encode_endpoint(x: f32, y: f32, clipx: f32, clipy: f32, clipw: f32, cliph: f3
     code
 }
 
-fn line_clipped(
+fn line_clipped<P: Painter>(
     mut x0: f32,
     mut y0: f32,
     mut x1: f32,
@@ -119,7 +229,7 @@ fn line_clipped(
     clipy: f32,
     clipw: f32,
     cliph: f32,
-    draw: &nannou::app::Draw,
+    painter: &P,
 ) -> bool {
     /* Stores encodings for the two endpoints of our line */
     //   int e0code, e1code;
@@ -191,13 +301,13 @@ fn line_clipped(
 
     //*!* Only draw the line if it was not rejected */
     if accept {
-        draw.line().start(pt2(x0, y0)).end(pt2(x1, y1)).color(WHITE);
+        painter.line(pt2(x0, y0), pt2(x1, y1), 1., rgba(1., 1., 1., 1.));
     }
 
     accept
 }
 
-fn draw_square(x: f32, y: f32, w: f32, step: f32, a: f32, draw: &nannou::app::Draw) {
+fn draw_square<P: Painter>(x: f32, y: f32, w: f32, step: f32, a: f32, painter: &P) {
     let xstart = x + w / 2.;
     let ystart = y + w / 2.;
     // let xstart = x + random_range(0., w);
@@ -217,13 +327,13 @@ fn draw_square(x: f32, y: f32, w: f32, step: f32, a: f32, draw: &nannou::app::Dr
         let mut y0 = slope * x0 + c + i as f32 * step / a.cos();
         let mut x1 = x + w + w / 2.;
         let mut y1 = slope * x1 + c + i as f32 * step / a.cos();
-        up_accept = line_clipped(x0, y0, x1, y1, x, y, w, w, draw);
+        up_accept = line_clipped(x0, y0, x1, y1, x, y, w, w, painter);
 
         x0 = x - w / 2.;
         y0 = slope * x0 + c - i as f32 * step / a.cos();
         x1 = x + w + w / 2.;
         y1 = slope * x1 + c - i as f32 * step / a.cos();
-        down_accept = line_clipped(x0, y0, x1, y1, x, y, w, w, draw);
+        down_accept = line_clipped(x0, y0, x1, y1, x, y, w, w, painter);
 
         i += 1;
     }
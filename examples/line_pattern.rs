@@ -1,5 +1,7 @@
 extern crate nannou;
 use nannou::prelude::*;
+use painter::Painter;
+use plotter::{Recorder, SvgOptions};
 
 const WIDTH: f32 = 1024.;
 const HEIGHT: f32 = 800.;
@@ -27,8 +29,16 @@ fn model(app: &App) -> Model {
 
 fn update(_app: &App, _model: &mut Model, _update: Update) {}
 
-fn window_event(_app: &App, _model: &mut Model, event: WindowEvent) {
+fn window_event(app: &App, _model: &mut Model, event: WindowEvent) {
     match event {
+        KeyPressed(Key::S) => {
+            let recorder = Recorder::new();
+            draw_pattern(&recorder, app.mouse.x, app.mouse.y);
+            let area = Rect::from_w_h(WIDTH, HEIGHT);
+            recorder
+                .save_svg("line_pattern.svg", area, &SvgOptions::default())
+                .unwrap();
+        }
         KeyPressed(_key) => {}
         KeyReleased(_key) => {}
         MouseMoved(_pos) => {}
@@ -62,17 +72,18 @@ fn view(app: &App, _model: &Model, frame: &Frame) {
     draw.to_frame(app, &frame).unwrap();
 }
 
-fn draw_pattern(draw: &nannou::app::Draw, w: f32, h: f32) {
+fn draw_pattern<P: Painter>(painter: &P, w: f32, h: f32) {
+    let color = rgba(1., 1., 1., 1.);
     let spacing = 5.;
     let mut i = -w;
     while i < h + w {
-        draw.line().start((i, 0.).into()).end((i + h, h).into());
+        painter.line(pt2(i, 0.), pt2(i + h, h), 1., color);
         i += spacing;
     }
 
     let mut i = h + w;
     while i >= -w {
-        draw.line().start((i, 0.).into()).end((i - h, h).into());
+        painter.line(pt2(i, 0.), pt2(i - h, h), 1., color);
         i -= spacing;
     }
 }
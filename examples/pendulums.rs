@@ -1,7 +1,16 @@
+use canvas::Canvas;
+use capture::{AnimationOptions, LoopDetector};
+use image::imageops::{self, FilterType};
+use image::RgbaImage;
 use nannou::prelude::*;
+use params::{Panel, Params};
+use std::time::Duration;
 
-const speed_relation: f32 = 2.;
 const center: Point2<f32> = Point2 { x: 0., y: 0. };
+// Every 4th update at half size. A cycle with the default parameters is about
+// 184 frames of 360x360, close to 100 MB held until it is saved.
+const RECORD_EVERY: usize = 4;
+const RECORD_SCALE: u32 = 2;
 
 fn main() {
     nannou::app(model).update(update).run();
@@ -11,18 +20,69 @@ struct Model {
     _window: window::Id,
     speed: f32,
     angle: f32,
-    paths: Vec<Vec<(Point2, Srgba<f32>)>>,
+    // Last two positions of every joint. Each step strokes through them, so
+    // the trace joins up like one polyline instead of butt-ended pieces.
+    tips: Vec<Vec<Point2>>,
+    canvas: Canvas,
     joints: usize,
     lenght: f32,
+    updates: usize,
+    recording: Option<Recording>,
+    panel: Panel<Model>,
+}
+
+// Frames since R was pressed, saved as soon as the trace comes back to the
+// first one.
+struct Recording {
+    frames: Vec<RgbaImage>,
+    detector: LoopDetector,
+}
+
+impl Recording {
+    fn new() -> Self {
+        Recording {
+            frames: Vec::new(),
+            detector: LoopDetector::new(0.002),
+        }
+    }
+
+    fn push(&mut self, canvas: &Canvas) -> Option<usize> {
+        let (w, h) = (
+            canvas.width() / RECORD_SCALE,
+            canvas.height() / RECORD_SCALE,
+        );
+        let frame = imageops::resize(&canvas.to_image(), w, h, FilterType::Triangle);
+        let found = self.detector.push(&frame);
+        self.frames.push(frame);
+        found
+    }
+
+    fn save(mut self, length: Option<usize>) {
+        if let Some(length) = length.or_else(|| self.detector.finish()) {
+            self.frames.truncate(length);
+            println!("loop of {} frames", length);
+        }
+        let options = AnimationOptions {
+            delay: Duration::from_millis(1000 * RECORD_EVERY as u64 / 60),
+            ..AnimationOptions::default()
+        };
+        capture::save_gif("pendulums.gif", &self.frames, &options)
+            .and_then(|_| capture::save_apng("pendulums.apng", &self.frames, &options))
+            .unwrap_or_else(|err| eprintln!("{}", err));
+    }
 }
 
 impl Model {
     fn refresh(&mut self) {
-        self.joints = 5;
+        let params = &self.panel.params;
+        self.joints = params.usize("joints");
+        self.lenght = params.f32("length");
+        let speed_relation = params.f32("speed_relation");
         let speed = 8. / 1.75.powf(self.joints as f32 - 1.) / 2f32.powf(speed_relation - 1.);
         self.speed = speed;
         self.angle = 0.;
-        self.paths = vec![Vec::new(); self.joints];
+        self.tips = vec![Vec::new(); self.joints];
+        self.canvas.clear();
     }
 }
 
@@ -35,19 +95,33 @@ fn model(app: &App) -> Model {
         .build()
         .unwrap();
 
-    let joints = 5;
-    let speed = 8. / 1.75.powf(joints as f32 - 1.) / 2f32.powf(speed_relation - 1.);
-    Model {
+    let params = Params::new()
+        .float("speed_relation", 2., 1.0..=4.)
+        .int("joints", 5, 1..=8)
+        .float("length", 100., 20.0..=150.)
+        .args();
+    let panel = Panel::new(app, _window, params).on_change(&[], Model::refresh);
+    let (w, h) = app.window_rect().w_h();
+    let canvas = Canvas::with_background(w as u32, h as u32, rgba(0.44, 0.5, 0.56, 1.));
+    let mut model = Model {
         _window,
-        paths: vec![Vec::new(); joints],
-        speed,
+        tips: Vec::new(),
+        canvas,
+        speed: 0.,
         angle: 0.,
-        lenght: 100.,
-        joints,
-    }
+        lenght: 0.,
+        joints: 0,
+        updates: 0,
+        recording: None,
+        panel,
+    };
+    model.refresh();
+    model
 }
 
-fn update(app: &App, model: &mut Model, _update: Update) {
+fn update(_app: &App, model: &mut Model, _update: Update) {
+    Panel::update(model, |model| &mut model.panel);
+
     let mut pos = center;
 
     for i in 0..model.joints {
@@ -63,7 +137,15 @@ fn update(app: &App, model: &mut Model, _update: Update) {
         let next_pos = rotate(next_pos, a) + pos;
         // dbg!(next_pos);
 
-        model.paths[i].push((next_pos, rgba(frac * 0.5, 0., 1. - frac, 1.)));
+        let color = rgba(frac * 0.5, 0., 1. - frac, 1.);
+        let tail = &mut model.tips[i];
+        tail.push(next_pos);
+        if tail.len() > 1 {
+            model.canvas.polyline(tail, 4., color);
+        }
+        if tail.len() > 2 {
+            tail.remove(0);
+        }
         pos = next_pos;
     }
 
@@ -72,11 +154,31 @@ fn update(app: &App, model: &mut Model, _update: Update) {
     if model.angle > TAU / 2. {
         model.refresh()
     }
+
+    model.updates += 1;
+    if model.updates % RECORD_EVERY == 0 {
+        if let Some(recording) = &mut model.recording {
+            if let Some(length) = recording.push(&model.canvas) {
+                model.recording.take().unwrap().save(Some(length));
+            }
+        }
+    }
 }
 
-fn window_event(_app: &App, _model: &mut Model, event: WindowEvent) {
+fn window_event(_app: &App, model: &mut Model, event: WindowEvent) {
     match event {
-        KeyPressed(_key) => {}
+        KeyPressed(key) => match key {
+            Key::S => {
+                model.canvas.save("pendulums.png").unwrap();
+                model.panel.params.save_beside("pendulums.png").unwrap();
+            }
+            Key::P => model.panel.toggle(),
+            Key::R => match model.recording.take() {
+                Some(recording) => recording.save(None),
+                None => model.recording = Some(Recording::new()),
+            },
+            _ => {}
+        },
         KeyReleased(_key) => {}
         MouseMoved(_pos) => {}
         MousePressed(_button) => {}
@@ -100,17 +202,13 @@ fn window_event(_app: &App, _model: &mut Model, event: WindowEvent) {
 fn view(app: &App, model: &Model, frame: &Frame) {
     // Prepare to draw.
     let draw = app.draw();
-    // Clear the background to pink.
     draw.background().color(SLATEGRAY);
-    let weight = 4.0;
-
-    for vertices in &model.paths {
-        draw.polyline()
-            .weight(weight)
-            .colored_points(vertices.clone());
-    }
+    // The trace so far lives in the canvas.
+    let (w, h) = app.window_rect().w_h();
+    draw.texture(&model.canvas.to_texture(app)).w_h(w, h);
 
     draw.to_frame(app, &frame).unwrap();
+    model.panel.draw(app, &frame);
 }
 
 fn rotate(p: Point2, a: f32) -> Point2 {
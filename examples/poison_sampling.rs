@@ -1,4 +1,5 @@
 use nannou::prelude::*;
+use params::{Panel, Params};
 
 static WIDTH: f32 = 720.;
 static HEIGHT: f32 = 720.;
@@ -10,6 +11,21 @@ fn main() {
 struct Model {
     _window: window::Id,
     poise: PoissonDisk,
+    panel: Panel<Model>,
+}
+
+impl Model {
+    // The grid depends on the radius, so that needs a new disk.
+    fn restart(&mut self) {
+        let params = &self.panel.params;
+        let (radius, num_samples) = (params.i64("radius"), params.i64("num_samples"));
+        self.poise = PoissonDisk::new(720, 720, radius as u32, num_samples as u32);
+    }
+
+    fn resample(&mut self) {
+        self.poise.num_samples = self.panel.params.i64("num_samples") as u32;
+        self.poise.reset();
+    }
 }
 
 fn model(app: &App) -> Model {
@@ -20,20 +36,33 @@ fn model(app: &App) -> Model {
         .event(window_event)
         .build()
         .unwrap();
+    // Dot size and speed are read every frame and apply as they are.
+    let params = Params::new()
+        .int("radius", 10, 3..=60)
+        .int("num_samples", 30, 1..=100)
+        .float("dot", 5., 1.0..=20.)
+        .int("speed", 10, 1..=100)
+        .args();
+    let panel = Panel::new(app, _window, params)
+        .on_change(&["radius"], Model::restart)
+        .on_change(&["num_samples"], Model::resample);
     Model {
         _window,
         poise: PoissonDisk::new(720, 720, 10, 30),
+        panel,
     }
 }
 
 fn update(_app: &App, model: &mut Model, _update: Update) {
-    for _ in 0..10 {
+    Panel::update(model, |model| &mut model.panel);
+    for _ in 0..model.panel.params.usize("speed") {
         model.poise.tick();
     }
 }
 
-fn window_event(_app: &App, _model: &mut Model, event: WindowEvent) {
+fn window_event(_app: &App, model: &mut Model, event: WindowEvent) {
     match event {
+        KeyPressed(Key::P) => model.panel.toggle(),
         KeyPressed(_key) => {}
         KeyReleased(_key) => {}
         MouseMoved(_pos) => {}
@@ -61,14 +90,16 @@ fn view(app: &App, model: &Model, frame: &Frame) {
     // Clear the background to pink.
     draw.background().color(PLUM);
     // Draw a red ellipse with default size and position.
+    let dot = model.panel.params.f32("dot");
     for p in &model.poise.samples {
         draw.ellipse()
             .x_y(p.0 as f32 - WIDTH / 2., p.1 as f32 - HEIGHT / 2.)
             .color(STEELBLUE)
-            .w_h(5., 5.);
+            .w_h(dot, dot);
     }
     // Write to the window frame.
     draw.to_frame(app, &frame).unwrap();
+    model.panel.draw(app, &frame);
 }
 
 pub struct Point(pub usize, pub usize);
@@ -213,8 +244,10 @@ impl PoissonDisk {
 
     pub fn reset(&mut self) {
         self.active.clear();
-        self.grid.clear();
         self.samples.clear();
+        // Clearing would leave nothing to index, the grid keeps its size.
+        self.grid.iter_mut().for_each(|cell| *cell = None);
+        self.cells.iter_mut().for_each(|cell| *cell = Cell::EMPTY);
 
         let point = (
             (random_range(0, self.width) as f64) as usize,
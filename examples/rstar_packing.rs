@@ -1,4 +1,6 @@
 use nannou::prelude::*;
+use painter::Painter;
+use plotter::{GcodeOptions, HpglOptions, Recorder};
 
 extern crate rstar;
 use rstar::Point;
@@ -86,8 +88,22 @@ fn update(app: &App, model: &mut Model, _update: Update) {
     }
 }
 
-fn window_event(_app: &App, _model: &mut Model, event: WindowEvent) {
+fn window_event(app: &App, model: &mut Model, event: WindowEvent) {
     match event {
+        KeyPressed(Key::G) => {
+            let recorder = outlines(model);
+            let options = GcodeOptions::default();
+            recorder
+                .save_gcode("rstar_packing.gcode", app.window_rect(), &options)
+                .unwrap_or_else(|err| eprintln!("{}", err));
+        }
+        KeyPressed(Key::H) => {
+            let recorder = outlines(model);
+            let options = HpglOptions::default();
+            recorder
+                .save_hpgl("rstar_packing.hpgl", app.window_rect(), &options)
+                .unwrap_or_else(|err| eprintln!("{}", err));
+        }
         KeyPressed(_key) => {}
         KeyReleased(_key) => {}
         MouseMoved(_pos) => {}
@@ -122,6 +138,15 @@ fn view(app: &App, model: &Model, frame: &Frame) {
     draw.to_frame(app, &frame).unwrap();
 }
 
+/// Circle outlines for the plotter, the fill colors don't matter there.
+fn outlines(model: &Model) -> Recorder {
+    let recorder = Recorder::new();
+    for c in &model.circles {
+        recorder.ellipse(pt2(c.x, c.y), 2. * c.r, 2. * c.r, rgba(0., 0., 0., 1.));
+    }
+    recorder
+}
+
 #[derive(Copy, Clone)]
 struct Circle {
     x: f32,
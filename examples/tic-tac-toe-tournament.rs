@@ -0,0 +1,26 @@
+//! Tic-tac-toe strategies playing each other, prints wins, draws, losses and
+//! think time per move.
+//!
+//! `cargo run --release --example tic-tac-toe-tournament -- --players
+//! perfect,minimax:3,mcts:2000,greedy --games 200 --seed 1` with `--book FILE`
+//! for openings and `--board`/`--k` for other grids.
+use tictactoe::Tournament;
+
+fn main() {
+    let tournament = Tournament::from_args();
+    let names: Vec<&str> = tournament
+        .entrants
+        .iter()
+        .map(|entrant| entrant.name.as_str())
+        .collect();
+    println!(
+        "{} games per pairing of {} on {}x{} k{}, seed {}",
+        tournament.games,
+        names.join(", "),
+        tournament.grid.columns,
+        tournament.grid.rows,
+        tournament.grid.k,
+        tournament.seed
+    );
+    print!("\n{}", tournament.run());
+}
@@ -0,0 +1,394 @@
+//! Tic-tac-toe in the terminal, the same game as `examples/tic-tac-toe`
+//! for when there is no window to open.
+//!
+//! Takes `--board`, `--k`, `--game`, `--host`, `--connect` and the `--ai`
+//! flags of the window version, `--eval` starts with the minimax scores of
+//! the empty cells shown.
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyModifiers};
+use crossterm::terminal::{self, ClearType, EnterAlternateScreen, LeaveAlternateScreen};
+use crossterm::{cursor, execute, queue, style::Print};
+use std::io::{self, Write};
+use std::time::Duration;
+use tictactoe::brain::Search;
+use tictactoe::{net, strategy, Board, BoardState, Difficulty, Field, Game, GameMode, Grid};
+use tictactoe::{Eval, Player, Remote, Strategy};
+
+/// How often the other player is checked on while waiting for keys.
+const TICK: Duration = Duration::from_millis(50);
+
+const HELP: &str = "cell number or arrows and enter to play, u/r undo and redo, \
+                    e scores, n menu, q quit";
+
+struct Tui {
+    board: Board,
+    mode: GameMode,
+    strategy: Box<dyn Strategy>,
+    /// Level picked on the menu, `None` while playing the `--ai` strategy.
+    difficulty: Option<Difficulty>,
+    cursor: usize,
+    /// Digits of a cell number typed so far.
+    typed: String,
+    show_scores: bool,
+    /// Scores of the empty cells and the moves they were searched after.
+    scores: Option<(Vec<usize>, Vec<Eval>)>,
+    /// The other player with `--host` or `--connect`.
+    remote: Option<Remote>,
+    /// Last thing worth telling about the connection.
+    news: String,
+}
+
+enum Action {
+    Continue,
+    Quit,
+}
+
+fn main() -> crossterm::Result<()> {
+    let (strategy, difficulty) = match strategy::from_args() {
+        Some(strategy) => (strategy, None),
+        None => (Difficulty::Medium.strategy(), Some(Difficulty::Medium)),
+    };
+    let board = match Game::from_args() {
+        Some(game) => Board::from_game(&game),
+        None => Board::new(Grid::from_args()),
+    };
+    let remote = net::from_args(board.grid);
+    let mode = match remote {
+        Some(_) => GameMode::Networked,
+        None => GameMode::Waiting,
+    };
+    let mut tui = Tui {
+        board,
+        mode,
+        strategy,
+        difficulty,
+        cursor: 0,
+        typed: String::new(),
+        show_scores: std::env::args().any(|arg| arg == "--eval"),
+        scores: None,
+        remote,
+        news: String::new(),
+    };
+
+    let mut stdout = io::stdout();
+    terminal::enable_raw_mode()?;
+    execute!(stdout, EnterAlternateScreen, cursor::Hide)?;
+    let result = tui.run(&mut stdout);
+    execute!(stdout, cursor::Show, LeaveAlternateScreen)?;
+    terminal::disable_raw_mode()?;
+    result
+}
+
+impl Tui {
+    fn run(&mut self, out: &mut impl Write) -> crossterm::Result<()> {
+        let mut redraw = true;
+        loop {
+            if self.mode == GameMode::SinglePlayer {
+                self.board.computer_move(self.strategy.as_mut());
+            }
+            if let Some(remote) = &mut self.remote {
+                for event in remote.poll(&mut self.board) {
+                    self.news = event.to_string();
+                    redraw = true;
+                }
+            }
+            if redraw {
+                self.draw(out)?;
+            }
+            // Anything else, like a resize, only redraws.
+            redraw = event::poll(TICK)?;
+            if redraw {
+                if let Event::Key(key) = event::read()? {
+                    if let Action::Quit = self.key(key) {
+                        return Ok(());
+                    }
+                }
+            }
+        }
+    }
+
+    fn draw(&mut self, out: &mut impl Write) -> crossterm::Result<()> {
+        let mut lines = Vec::new();
+        match self.mode {
+            GameMode::Waiting => {
+                let level = match self.difficulty {
+                    Some(difficulty) => difficulty.to_string(),
+                    None => "Custom".to_string(),
+                };
+                lines.push("1  single player".to_string());
+                lines.push("2  multiplayer".to_string());
+                lines.push(format!("d  difficulty: {}", level));
+                lines.push("q  quit".to_string());
+            }
+            _ => {
+                lines.extend(render(&self.board, Some(self.cursor)));
+                lines.push(String::new());
+                lines.push(match self.board.state {
+                    BoardState::Winner(player, _) => {
+                        format!(
+                            "{} wins! enter for the menu",
+                            Field::from(player).to_string()
+                        )
+                    }
+                    BoardState::Tie => "Tie! enter for the menu".to_string(),
+                    BoardState::InGame => format!(
+                        "{} to move {}",
+                        Field::from(self.board.current_player).to_string(),
+                        self.typed
+                    ),
+                });
+                if let Some(remote) = &self.remote {
+                    lines.push(format!("{}, {}", remote.status(&self.board), self.news));
+                }
+                if self.show_scores && self.board.state == BoardState::InGame {
+                    lines.push(String::new());
+                    lines.push("scores for the player to move:".to_string());
+                    self.search_scores();
+                    let (_, scores) = self.scores.as_ref().unwrap();
+                    lines.extend(render_scores(&self.board, scores));
+                }
+                lines.push(String::new());
+                lines.push(HELP.to_string());
+            }
+        }
+        queue!(out, cursor::MoveTo(0, 0), terminal::Clear(ClearType::All))?;
+        for line in lines {
+            queue!(out, Print(line), Print("\r\n"))?;
+        }
+        out.flush()?;
+        Ok(())
+    }
+
+    /// Minimax scores of the current position, searched again only after a
+    /// move.
+    fn search_scores(&mut self) {
+        let board = &self.board;
+        if self.scores.as_ref().map(|(history, _)| history) != Some(&board.history) {
+            let scores = Search::new(board.grid).move_scores(&board.board, board.current_player);
+            self.scores = Some((board.history.clone(), scores));
+        }
+    }
+
+    fn key(&mut self, key: KeyEvent) -> Action {
+        let quit = key.code == KeyCode::Char('q')
+            || key.code == KeyCode::Esc
+            || key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL);
+        if quit {
+            return Action::Quit;
+        }
+        if self.mode == GameMode::Waiting {
+            match key.code {
+                KeyCode::Char('1') => self.mode = GameMode::SinglePlayer,
+                KeyCode::Char('2') => self.mode = GameMode::MultiPlayer,
+                KeyCode::Char('d') => {
+                    let difficulty = self.difficulty.map_or(Difficulty::Easy, Difficulty::next);
+                    self.difficulty = Some(difficulty);
+                    self.strategy = difficulty.strategy();
+                }
+                _ => {}
+            }
+            return Action::Continue;
+        }
+        if self.board.state != BoardState::InGame {
+            if let KeyCode::Enter | KeyCode::Char(' ') | KeyCode::Char('n') = key.code {
+                self.new_game();
+            }
+            return Action::Continue;
+        }
+
+        let grid = self.board.grid;
+        let (column, row) = grid.position(self.cursor);
+        match key.code {
+            KeyCode::Left if column > 0 => self.cursor -= 1,
+            KeyCode::Right if column + 1 < grid.columns => self.cursor += 1,
+            KeyCode::Up if row > 0 => self.cursor -= grid.columns,
+            KeyCode::Down if row + 1 < grid.rows => self.cursor += grid.columns,
+            KeyCode::Char(digit) if digit.is_ascii_digit() => {
+                self.typed.push(digit);
+                // Play as soon as no further digit could make a cell.
+                if let Ok(number) = self.typed.parse::<usize>() {
+                    if number * 10 > grid.len() {
+                        self.play_typed();
+                    }
+                }
+            }
+            KeyCode::Backspace => {
+                self.typed.pop();
+            }
+            KeyCode::Enter | KeyCode::Char(' ') => {
+                if self.typed.is_empty() {
+                    self.play(self.cursor);
+                } else {
+                    self.play_typed();
+                }
+            }
+            KeyCode::Char('u') => self.undo(),
+            KeyCode::Char('r') => self.redo(),
+            KeyCode::Char('e') => self.show_scores = !self.show_scores,
+            KeyCode::Char('n') => self.new_game(),
+            _ => {}
+        }
+        Action::Continue
+    }
+
+    fn play(&mut self, cell: usize) -> bool {
+        match &mut self.remote {
+            Some(remote) => remote.play(&mut self.board, cell),
+            None => self.board.play(cell),
+        }
+    }
+
+    fn play_typed(&mut self) {
+        if let Some(cell) = parse_cell(&self.typed, &self.board.grid) {
+            if self.play(cell) {
+                self.cursor = cell;
+            }
+        }
+        self.typed.clear();
+    }
+
+    /// Takes back a move, in single player the computer's answer with it.
+    /// Networked games can't.
+    fn undo(&mut self) {
+        if self.mode == GameMode::Networked {
+            return;
+        }
+        if self.board.undo()
+            && self.mode == GameMode::SinglePlayer
+            && self.board.current_player == Player::Computer
+        {
+            self.board.undo();
+        }
+    }
+
+    fn redo(&mut self) {
+        if self.mode == GameMode::Networked {
+            return;
+        }
+        if self.board.redo()
+            && self.mode == GameMode::SinglePlayer
+            && self.board.current_player == Player::Computer
+        {
+            self.board.redo();
+        }
+    }
+
+    /// Back to the menu, or a fresh board on both sides over the network.
+    fn new_game(&mut self) {
+        match &mut self.remote {
+            Some(remote) => remote.new_game(&mut self.board),
+            None => {
+                self.mode = GameMode::Waiting;
+                self.board = Board::new(self.board.grid);
+            }
+        }
+        self.cursor = 0;
+        self.typed.clear();
+    }
+}
+
+/// Cells are numbered from 1 in the top left, row by row.
+fn parse_cell(number: &str, grid: &Grid) -> Option<usize> {
+    let number: usize = number.parse().ok()?;
+    if number >= 1 && number <= grid.len() {
+        Some(number - 1)
+    } else {
+        None
+    }
+}
+
+/// Marks as `Field::to_string` writes them, empty cells by number and
+/// `cursor` in brackets.
+fn render(board: &Board, cursor: Option<usize>) -> Vec<String> {
+    let labels = board
+        .board
+        .iter()
+        .enumerate()
+        .map(|(cell, field)| match field {
+            Field::Empty => (cell + 1).to_string(),
+            _ => field.to_string(),
+        })
+        .collect();
+    table(&board.grid, labels, cursor)
+}
+
+/// Scores by cell, positive when good for the player to move and the marks
+/// where cells are taken.
+fn render_scores(board: &Board, scores: &[Eval]) -> Vec<String> {
+    let mut labels: Vec<String> = board.board.iter().map(Field::to_string).collect();
+    for eval in scores {
+        let score = match board.current_player {
+            Player::Computer => eval.score,
+            Player::Human => -eval.score,
+        };
+        labels[eval.position] = match score {
+            0 => "0".to_string(),
+            _ => format!("{:+}", score),
+        };
+    }
+    table(&board.grid, labels, None)
+}
+
+fn table(grid: &Grid, labels: Vec<String>, cursor: Option<usize>) -> Vec<String> {
+    let width = labels.iter().map(String::len).max().unwrap_or(1);
+    let separator = vec!["-".repeat(width + 2); grid.columns].join("+");
+    let mut lines = Vec::new();
+    for row in 0..grid.rows {
+        if row > 0 {
+            lines.push(separator.clone());
+        }
+        let cells: Vec<String> = (0..grid.columns)
+            .map(|column| {
+                let cell = grid.cell(column, row);
+                if Some(cell) == cursor {
+                    format!("[{:^w$}]", labels[cell], w = width)
+                } else {
+                    format!(" {:^w$} ", labels[cell], w = width)
+                }
+            })
+            .collect();
+        lines.push(cells.join("|"));
+    }
+    lines
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn renders_boards() {
+        let board = Board::from_game(&Game::parse("3x3\nb2 a1").unwrap());
+        assert_eq!(
+            render(&board, Some(8)),
+            vec![
+                " O | 2 | 3 ",
+                "---+---+---",
+                " 4 | X | 6 ",
+                "---+---+---",
+                " 7 | 8 |[9]"
+            ]
+        );
+        let board = Board::new(Grid::new(4, 3, 3));
+        assert_eq!(render(&board, None)[4], " 9  | 10 | 11 | 12 ");
+    }
+
+    #[test]
+    fn renders_scores() {
+        let board = Board::from_game(&Game::parse("3x3\na1 a2 b1 b2").unwrap());
+        let scores = Search::new(board.grid).move_scores(&board.board, board.current_player);
+        let lines = render_scores(&board, &scores);
+        // X wins on c1, blocking on c2 still draws and anything else loses.
+        assert_eq!(lines[0], "  X  |  X  | +10 ");
+        assert_eq!(lines[2], "  O  |  O  |  0  ");
+        assert!(lines[4].split('|').all(|cell| cell.trim().starts_with('-')));
+    }
+
+    #[test]
+    fn parses_cell_numbers() {
+        let grid = Grid::default();
+        assert_eq!(parse_cell("1", &grid), Some(0));
+        assert_eq!(parse_cell("9", &grid), Some(8));
+        assert_eq!(parse_cell("0", &grid), None);
+        assert_eq!(parse_cell("10", &grid), None);
+    }
+}
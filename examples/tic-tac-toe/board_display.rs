@@ -1,200 +1,195 @@
-use crate::board::{Board, BoardState};
-use crate::field::Field;
-use crate::player::Player;
+//! Drawing a `tictactoe::Board` and finding the cell under the mouse, the
+//! board fills the rect it's given. Marks and the winning line draw
+//! themselves in over time, a `Timeline` knows since when.
 use nannou::prelude::*;
+use tictactoe::{Board, BoardState, Field, Grid, Player};
+use tween::{Ease, Tween};
 
-impl Board {
-    pub fn show_selections(&self, draw: &app::Draw, rect: &Rect) {
-        let dims = (rect.right() - rect.left()) / 3.0;
-        let draw_text = |sigil: &str, location: &Rect| {
-            let text = text(sigil).font_size(dims as u32).build(*location);
-            draw.path().fill().color(BLACK).events(text.path_events());
-        };
-        self.board.iter().enumerate().for_each(|(i, v)| {
-            if *v != Field::Empty {
-                match (i, v) {
-                    (0, v) => {
-                        let location = Rect::from_x_y_w_h(
-                            rect.left() + dims / 2.0,
-                            rect.top() - dims / 3.0,
-                            dims,
-                            dims,
-                        );
-                        draw_text(&v.to_string(), &location);
-                    }
-                    (1, v) => {
-                        let location = Rect::from_x_y_w_h(
-                            rect.right() / 3.0 - dims / 2.0,
-                            rect.top() - dims / 3.0,
-                            dims,
-                            dims,
-                        );
-                        draw_text(&v.to_string(), &location);
-                    }
-                    (2, v) => {
-                        let location = Rect::from_x_y_w_h(
-                            rect.right() - dims / 2.0,
-                            rect.top() - dims / 3.0,
-                            dims,
-                            dims,
-                        );
-                        draw_text(&v.to_string(), &location);
-                    }
-                    (3, v) => {
-                        let location = Rect::from_x_y_w_h(
-                            rect.left() + dims / 2.0,
-                            rect.top() / 3.0 - dims / 3.0,
-                            dims,
-                            dims,
-                        );
-                        draw_text(&v.to_string(), &location);
-                    }
-                    (4, v) => {
-                        let location = Rect::from_x_y_w_h(
-                            rect.left() / 3.0 + dims / 2.0,
-                            rect.top() / 3.0 - dims / 3.0,
-                            dims,
-                            dims,
-                        );
-                        draw_text(&v.to_string(), &location);
-                    }
-                    (5, v) => {
-                        let location = Rect::from_x_y_w_h(
-                            rect.right() - dims / 2.0,
-                            rect.top() / 3.0 - dims / 3.0,
-                            dims,
-                            dims,
-                        );
-                        draw_text(&v.to_string(), &location);
-                    }
-                    (6, v) => {
-                        let location = Rect::from_x_y_w_h(
-                            rect.left() + dims / 2.0,
-                            rect.bottom() / 3.0 - dims / 3.0,
-                            dims,
-                            dims,
-                        );
-                        draw_text(&v.to_string(), &location);
-                    }
-                    (7, v) => {
-                        let location = Rect::from_x_y_w_h(
-                            rect.right() / 3.0 - dims / 2.0,
-                            rect.bottom() / 3.0 - dims / 3.0,
-                            dims,
-                            dims,
-                        );
-                        draw_text(&v.to_string(), &location);
-                    }
-                    (8, v) => {
-                        let location = Rect::from_x_y_w_h(
-                            rect.right() - dims / 2.0,
-                            rect.bottom() / 3.0 - dims / 3.0,
-                            dims,
-                            dims,
-                        );
-                        draw_text(&v.to_string(), &location);
-                    }
-                    _ => unimplemented!(),
-                };
-            }
-        });
-    }
-    fn show_winner(&self, draw: &app::Draw, rect: &Rect, win: (usize, usize, usize)) {
-        let start_x;
-        let start_y;
-        let end_x;
-        let end_y;
-        let (top, _, bottom) = win;
-        match (top, bottom) {
-            // top row
-            (0, 2) => {
-                start_x = rect.left();
-                start_y = rect.top() - rect.top() / 3.0;
-                end_x = rect.right();
-                end_y = rect.top() - rect.top() / 3.0;
-            }
-            // left column
-            (0, 6) => {
-                start_x = rect.left() - rect.left() / 3.0;
-                start_y = rect.top();
-                end_x = rect.left() - rect.left() / 3.0;
-                end_y = rect.bottom();
-            }
-            // middle row
-            (3, 5) => {
-                start_x = rect.left();
-                start_y = 0.0;
-                end_x = rect.right();
-                end_y = 0.0;
-            }
-            // middle column
-            (1, 7) => {
-                start_x = 0.0;
-                start_y = rect.top();
-                end_x = 0.0;
-                end_y = rect.bottom();
-            }
-            // bottom row
-            (6, 8) => {
-                start_x = rect.left();
-                start_y = rect.bottom() - rect.bottom() / 3.0;
-                end_x = rect.right();
-                end_y = rect.bottom() - rect.bottom() / 3.0;
-            }
-            // right column
-            (2, 8) => {
-                start_x = rect.right() - rect.right() / 3.0;
-                start_y = rect.top();
-                end_x = rect.right() - rect.right() / 3.0;
-                end_y = rect.bottom();
-            }
-            // left diag
-            (0, 8) => {
-                start_x = rect.left();
-                start_y = rect.top();
-                end_x = rect.right();
-                end_y = rect.bottom();
-            }
-            // right diag
-            (2, 6) => {
-                start_x = rect.right();
-                start_y = rect.top();
-                end_x = rect.left();
-                end_y = rect.bottom();
-            }
-            _ => unreachable!(),
+/// Seconds for a mark to draw itself in.
+const MARK: f32 = 0.3;
+/// Seconds for the winning line to run across its cells.
+const LINE: f32 = 0.5;
+
+/// When the moves on the board showed up and when the game ended.
+#[derive(Debug, Default)]
+pub struct Timeline {
+    moves: Vec<(usize, f32)>,
+    ended: Option<f32>,
+}
+
+impl Timeline {
+    /// Catches up with `board`, moves new since the last call start at
+    /// `time` and taken back ones are forgotten.
+    pub fn update(&mut self, board: &Board, time: f32) {
+        let kept = self
+            .moves
+            .iter()
+            .zip(&board.history)
+            .take_while(|((seen, _), cell)| seen == *cell)
+            .count();
+        self.moves.truncate(kept);
+        self.moves
+            .extend(board.history[kept..].iter().map(|&cell| (cell, time)));
+        if board.state == BoardState::InGame {
+            self.ended = None;
+        } else if self.ended.is_none() {
+            self.ended = Some(time);
         }
+    }
+
+    /// The mark in `cell`, one the timeline hasn't seen is already drawn.
+    fn mark(&self, cell: usize) -> Tween {
+        let start = self
+            .moves
+            .iter()
+            .find(|(seen, _)| *seen == cell)
+            .map_or(f32::NEG_INFINITY, |&(_, time)| time);
+        Tween::new(start, MARK).ease(Ease::OutQuad)
+    }
+
+    /// The winning line, once the last mark is in.
+    fn line(&self) -> Tween {
+        let start = self.ended.unwrap_or(f32::NEG_INFINITY);
+        Tween::new(start, LINE).ease(Ease::InOutCubic).delay(MARK)
+    }
+}
+
+/// Where `cell` is drawn.
+pub fn cell_rect(grid: &Grid, rect: &Rect, cell: usize) -> Rect {
+    let (column, row) = grid.position(cell);
+    let w = rect.w() / grid.columns as f32;
+    let h = rect.h() / grid.rows as f32;
+    Rect::from_x_y_w_h(
+        rect.left() + (column as f32 + 0.5) * w,
+        rect.top() - (row as f32 + 0.5) * h,
+        w,
+        h,
+    )
+}
+
+pub fn cell_at(grid: &Grid, rect: &Rect, point: Point2) -> Option<usize> {
+    if !rect.contains(point) {
+        return None;
+    }
+    let column = (point.x - rect.left()) / rect.w() * grid.columns as f32;
+    let row = (rect.top() - point.y) / rect.h() * grid.rows as f32;
+    let column = (column as usize).min(grid.columns - 1);
+    let row = (row as usize).min(grid.rows - 1);
+    Some(grid.cell(column, row))
+}
+
+pub fn show_grid(draw: &app::Draw, grid: &Grid, rect: &Rect) {
+    let w = rect.w() / grid.columns as f32;
+    let h = rect.h() / grid.rows as f32;
+    for column in 1..grid.columns {
+        let x = rect.left() + column as f32 * w;
         draw.line()
-            .start(pt2(start_x, start_y))
-            .end(pt2(end_x, end_y))
+            .start(pt2(x, rect.top()))
+            .end(pt2(x, rect.bottom()))
             .stroke_weight(2.0)
-            .color(BLACK);
+            .color(DARKGREY);
     }
-    pub fn display(&self, draw: &app::Draw, rect: &Rect) {
-        match &self.state {
-            BoardState::Tie => {
-                self.show_selections(draw, rect);
-                let location = rect.pad(20.0);
-                let wins = "Tie!".to_string();
-                let text = text(&wins).font_size(75).build(location);
-                draw.path().fill().color(WHITE).events(text.path_events());
-            }
-            BoardState::InGame => {
-                self.show_selections(draw, rect);
-            }
-            winner => {
-                self.show_selections(draw, rect);
-                let (winning_player, winning_pos) = match winner {
-                    BoardState::Winner(Player::Human, x) => (Field::X, x),
-                    BoardState::Winner(Player::Computer, x) => (Field::O, x),
-                    _ => unreachable!(),
-                };
-                self.show_winner(draw, rect, *winning_pos);
-                let location = rect.pad(20.0);
-                let wins = format!("{} Wins!", &winning_player.to_string());
-                let text = text(&wins).font_size(75).build(location);
-                draw.path().fill().color(WHITE).events(text.path_events());
+    for row in 1..grid.rows {
+        let y = rect.top() - row as f32 * h;
+        draw.line()
+            .start(pt2(rect.left(), y))
+            .end(pt2(rect.right(), y))
+            .stroke_weight(2.0)
+            .color(DARKGREY);
+    }
+}
+
+/// X as two strokes one after the other, O as a circle sweeping round from
+/// the top, `progress` of the way drawn.
+fn show_mark(draw: &app::Draw, field: Field, cell: &Rect, progress: f32) {
+    const SEGMENTS: usize = 48;
+    if progress <= 0.0 {
+        return;
+    }
+    let center = cell.xy();
+    let radius = cell.w().min(cell.h()) * 0.3;
+    let weight = radius / 5.0;
+    match field {
+        Field::X => {
+            let strokes = [
+                (pt2(-radius, radius), pt2(radius, -radius)),
+                (pt2(radius, radius), pt2(-radius, -radius)),
+            ];
+            for (i, &(start, end)) in strokes.iter().enumerate() {
+                let drawn = (progress * 2.0 - i as f32).clamp(0.0, 1.0);
+                if drawn > 0.0 {
+                    draw.line()
+                        .start(center + start)
+                        .end(center + start.lerp(end, drawn))
+                        .stroke_weight(weight)
+                        .color(BLACK);
+                }
             }
         }
+        Field::O => {
+            let points = (0..=SEGMENTS).map(|i| {
+                let angle = PI / 2.0 - TAU * progress * i as f32 / SEGMENTS as f32;
+                center + pt2(angle.cos(), angle.sin()) * radius
+            });
+            draw.polyline().weight(weight).points(points).color(BLACK);
+        }
+        Field::Empty => {}
     }
 }
+
+pub fn show_selections(
+    draw: &app::Draw,
+    board: &Board,
+    rect: &Rect,
+    timeline: &Timeline,
+    time: f32,
+) {
+    board.board.iter().enumerate().for_each(|(i, v)| {
+        if *v != Field::Empty {
+            let cell = cell_rect(&board.grid, rect, i);
+            show_mark(draw, *v, &cell, timeline.mark(i).progress(time));
+        }
+    });
+}
+
+/// The line through the winning cells, `progress` of the way along.
+fn show_winner(draw: &app::Draw, board: &Board, rect: &Rect, win: (usize, usize), progress: f32) {
+    if progress <= 0.0 {
+        return;
+    }
+    let grid = &board.grid;
+    let (first, last) = (cell_rect(grid, rect, win.0), cell_rect(grid, rect, win.1));
+    // Run on by half a cell at both ends.
+    let step = (last.xy() - first.xy()) / (grid.k - 1).max(1) as f32;
+    let (start, end) = (first.xy() - step / 2.0, last.xy() + step / 2.0);
+    draw.line()
+        .start(start)
+        .end(start.lerp(end, progress))
+        .stroke_weight(2.0)
+        .color(BLACK);
+}
+
+/// The marks, then once they're in the winning line and who won.
+pub fn display(draw: &app::Draw, board: &Board, rect: &Rect, timeline: &Timeline, time: f32) {
+    show_selections(draw, board, rect, timeline, time);
+    let line = timeline.line();
+    let message = match &board.state {
+        BoardState::InGame => return,
+        BoardState::Tie if !line.started(time) => return,
+        BoardState::Tie => "Tie!".to_string(),
+        BoardState::Winner(player, winning_pos) => {
+            show_winner(draw, board, rect, *winning_pos, line.progress(time));
+            if !line.done(time) {
+                return;
+            }
+            let winning_player = match player {
+                Player::Human => Field::X,
+                Player::Computer => Field::O,
+            };
+            format!("{} Wins!", &winning_player.to_string())
+        }
+    };
+    let location = rect.pad(20.0);
+    let text = text(&message).font_size(75).build(location);
+    draw.path().fill().color(WHITE).events(text.path_events());
+}
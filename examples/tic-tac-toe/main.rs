@@ -1,17 +1,12 @@
 use nannou::prelude::*;
-mod board;
 mod board_display;
-mod brain;
-mod eval;
-mod field;
 mod model;
-mod player;
-use board::Board;
-pub use field::Field;
-use model::{GameMode, Model};
+use board_display::Timeline;
+use model::Model;
+use tictactoe::{net, strategy, Board, Difficulty, Game, GameMode, Grid, Player};
 
 fn main() {
-    nannou::app(model).view(view).run();
+    nannou::app(model).update(update).view(view).run();
 }
 
 fn model(app: &App) -> Model {
@@ -20,38 +15,85 @@ fn model(app: &App) -> Model {
         .event(window_event)
         .build()
         .unwrap();
+    let (strategy, difficulty) = match strategy::from_args() {
+        Some(strategy) => (strategy, None),
+        None => (Difficulty::Medium.strategy(), Some(Difficulty::Medium)),
+    };
+    let board = match Game::from_args() {
+        Some(game) => Board::from_game(&game),
+        None => Board::new(Grid::from_args()),
+    };
+    let remote = net::from_args(board.grid);
+    let mode = match remote {
+        Some(_) => GameMode::Networked,
+        None => GameMode::Waiting,
+    };
     Model {
-        board: Board::new(app.window_rect()),
-        mode: GameMode::Waiting,
+        board,
+        mode,
+        strategy,
+        difficulty,
+        replay: None,
+        remote,
+        news: String::new(),
+        timeline: Timeline::default(),
+        fade: None,
     }
 }
 
+fn update(app: &App, model: &mut Model, _update: Update) {
+    model.poll();
+    model.animate(app.time);
+}
+
 fn view(app: &App, model: &Model, frame: &Frame) {
     frame.clear(SKYBLUE);
     let draw = app.draw();
-    model.display(&draw, &app.window_rect());
+    model.display(&draw, &app.window_rect(), app.time);
     draw.to_frame(app, &frame).unwrap();
 }
 
+/// Where `E` saves the game.
+const SAVE: &str = "tic-tac-toe.txt";
+
+/// `Z` and `Y` undo and redo, `R` replays the game, `E` saves it to `SAVE`
+/// and dropping a saved game on the window loads it.
 fn window_event(app: &App, model: &mut Model, event: WindowEvent) {
-    if model.mode == GameMode::SinglePlayer {
-        if let player::Player::Computer = model.board.current_player {
-            model.board.computer_move();
+    // The board stays as it is while a replay runs.
+    if model.mode == GameMode::SinglePlayer && model.replay.is_none() {
+        if let Player::Computer = model.board.current_player {
+            model.board.computer_move(model.strategy.as_mut());
         }
     }
     match event {
         MousePressed(_button) => match model.mode {
             GameMode::Waiting => {
-                model.mode = model.check_mode(&app.window_rect(), app.mouse.position());
+                let (rect, mouse) = (app.window_rect(), app.mouse.position());
+                if !model.check_difficulty(&rect, mouse) {
+                    model.mode = model.check_mode(&rect, mouse);
+                }
             }
             _ => {
                 model.check_new(app);
             }
         },
-        Resized(_size) => {
-            model.board = Board::new(app.window_rect());
-        }
-
+        KeyPressed(key) if model.mode != GameMode::Waiting => match key {
+            Key::Z => model.undo(),
+            Key::Y => model.redo(),
+            Key::R => model.start_replay(app.time),
+            Key::E => {
+                let game = model.board.game();
+                match game.save(SAVE) {
+                    Ok(()) => println!("saved to {}\n{}", SAVE, game),
+                    Err(err) => eprintln!("can't save {}: {}", SAVE, err),
+                }
+            }
+            _ => {}
+        },
+        DroppedFile(path) => match Game::load(&path) {
+            Ok(game) => model.load(&game),
+            Err(err) => eprintln!("{}", err),
+        },
         _ => {}
     }
 }
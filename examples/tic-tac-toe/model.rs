@@ -1,28 +1,142 @@
-use crate::board::{Board, BoardState};
+use crate::board_display::{self, Timeline};
 use nannou::prelude::*;
-
-#[derive(Debug, PartialEq, Eq)]
-pub enum GameMode {
-    SinglePlayer,
-    MultiPlayer,
-    Waiting,
-}
+use tictactoe::{Board, BoardState, Difficulty, Game, GameMode, Player, Remote, Strategy};
+use tween::{Ease, Tween};
 
 #[derive(Debug)]
 pub struct Model {
     pub board: Board,
     pub mode: GameMode,
+    pub strategy: Box<dyn Strategy>,
+    /// Level picked on the menu, `None` while playing the `--ai` strategy.
+    pub difficulty: Option<Difficulty>,
+    /// `app.time` when the replay of the game started, `None` while playing.
+    pub replay: Option<f32>,
+    /// The other player with `--host` or `--connect`.
+    pub remote: Option<Remote>,
+    /// Last thing worth telling about the connection.
+    pub news: String,
+    /// When the marks on the shown board appeared.
+    pub timeline: Timeline,
+    /// `app.time` when a finished game started fading to the menu.
+    pub fade: Option<f32>,
+}
+
+/// Seconds between the moves of a replay.
+const REPLAY_STEP: f32 = 0.6;
+/// Seconds from a finished game to the menu, which takes over halfway.
+const FADE: f32 = 0.8;
+
+/// Below the single player button, clicks step through the levels.
+fn difficulty_button(rect: &Rect) -> Rect {
+    let width = 150.0;
+    let height = width / 1.618;
+    Rect::from_x_y_w_h(rect.left() / 3.0, -height, width, height / 2.0)
 }
 
 impl Model {
     pub fn check_new(&mut self, app: &App) {
+        if self.replay.take().is_some() {
+            return;
+        }
+        if let Some(remote) = &mut self.remote {
+            if self.board.state != BoardState::InGame {
+                remote.new_game(&mut self.board);
+            } else if let Some(cell) =
+                board_display::cell_at(&self.board.grid, &app.window_rect(), app.mouse.position())
+            {
+                remote.play(&mut self.board, cell);
+            }
+            return;
+        }
         if self.board.state != BoardState::InGame {
-            self.mode = GameMode::Waiting;
-            self.board = Board::new(app.window_rect());
-        } else {
-            self.board.register_click(&app);
+            self.fade.get_or_insert(app.time);
+        } else if let Some(cell) =
+            board_display::cell_at(&self.board.grid, &app.window_rect(), app.mouse.position())
+        {
+            self.board.play(cell);
+        }
+    }
+    /// Takes in the other player's moves.
+    pub fn poll(&mut self) {
+        if let Some(remote) = &mut self.remote {
+            for event in remote.poll(&mut self.board) {
+                self.news = event.to_string();
+            }
         }
     }
+    /// Moves the animations on to `time`.
+    pub fn animate(&mut self, time: f32) {
+        if let Some(start) = self.fade {
+            if time >= start + FADE / 2.0 && self.mode != GameMode::Waiting {
+                self.mode = GameMode::Waiting;
+                self.board = Board::new(self.board.grid);
+            }
+            if time >= start + FADE {
+                self.fade = None;
+            }
+        }
+        let replay = self.replayed(time);
+        self.timeline
+            .update(replay.as_ref().unwrap_or(&self.board), time);
+    }
+    /// The board part way through a replay, `None` while playing.
+    fn replayed(&self, time: f32) -> Option<Board> {
+        self.replay
+            .map(|start| self.board.replay(((time - start) / REPLAY_STEP) as usize))
+    }
+    /// Takes back a move, in single player the computer's answer with it.
+    /// Networked games can't.
+    pub fn undo(&mut self) {
+        self.replay = None;
+        self.fade = None;
+        if self.mode == GameMode::Networked {
+            return;
+        }
+        if self.board.undo()
+            && self.mode == GameMode::SinglePlayer
+            && self.board.current_player == Player::Computer
+        {
+            self.board.undo();
+        }
+    }
+    pub fn redo(&mut self) {
+        self.replay = None;
+        self.fade = None;
+        if self.mode == GameMode::Networked {
+            return;
+        }
+        if self.board.redo()
+            && self.mode == GameMode::SinglePlayer
+            && self.board.current_player == Player::Computer
+        {
+            self.board.redo();
+        }
+    }
+    /// Plays the game back from the empty board, one move per `REPLAY_STEP`.
+    pub fn start_replay(&mut self, time: f32) {
+        if self.mode != GameMode::Waiting && !self.board.history.is_empty() {
+            self.replay = Some(time);
+        }
+    }
+    pub fn load(&mut self, game: &Game) {
+        if self.mode == GameMode::Networked {
+            eprintln!("can't load a game while playing over the network");
+            return;
+        }
+        self.replay = None;
+        self.fade = None;
+        self.board = Board::from_game(game);
+    }
+    pub fn check_difficulty(&mut self, rect: &Rect, mouse: Point2) -> bool {
+        if !difficulty_button(rect).contains(mouse) {
+            return false;
+        }
+        let difficulty = self.difficulty.map_or(Difficulty::Easy, Difficulty::next);
+        self.difficulty = Some(difficulty);
+        self.strategy = difficulty.strategy();
+        true
+    }
     pub fn check_mode(&self, rect: &Rect, mouse: Point2) -> GameMode {
         let x_single_player = rect.left() / 3.0;
         let y_single_player = 0.0;
@@ -42,7 +156,7 @@ impl Model {
         };
         GameMode::Waiting
     }
-    pub fn display(&self, draw: &app::Draw, rect: &Rect) {
+    pub fn display(&self, draw: &app::Draw, rect: &Rect, time: f32) {
         match self.mode {
             GameMode::Waiting => {
                 let x_single_player = rect.left() / 3.0;
@@ -69,34 +183,40 @@ impl Model {
                 draw.path().fill().color(BLACK).events(spt.path_events());
                 let mpt = text("Mutliplayer").font_size(20).build(multi_player);
                 draw.path().fill().color(BLACK).events(mpt.path_events());
+
+                let difficulty = difficulty_button(rect);
+                draw.rect()
+                    .x_y(difficulty.x(), difficulty.y())
+                    .w_h(difficulty.w(), difficulty.h())
+                    .color(GREY);
+                let level = match self.difficulty {
+                    Some(difficulty) => difficulty.to_string(),
+                    None => "Custom".to_string(),
+                };
+                let dt = text(&level).font_size(16).build(difficulty);
+                draw.path().fill().color(BLACK).events(dt.path_events());
             }
             _ => {
-                // right vertical line
-                draw.line()
-                    .start(pt2(rect.right() / 3.0, rect.top()))
-                    .end(pt2(rect.right() / 3.0, rect.bottom()))
-                    .stroke_weight(2.0)
-                    .color(DARKGREY);
-                // left vertical line
-                draw.line()
-                    .start(pt2(rect.left() / 3.0, rect.top()))
-                    .end(pt2(rect.left() / 3.0, rect.bottom()))
-                    .stroke_weight(2.0)
-                    .color(DARKGREY);
-                // bottom horizontal line
-                draw.line()
-                    .start(pt2(rect.left(), rect.bottom() / 3.0))
-                    .end(pt2(rect.right(), rect.bottom() / 3.0))
-                    .stroke_weight(2.0)
-                    .color(DARKGREY);
-                // top horizontal line
-                draw.line()
-                    .start(pt2(rect.left(), rect.top() / 3.0))
-                    .end(pt2(rect.right(), rect.top() / 3.0))
-                    .stroke_weight(2.0)
-                    .color(DARKGREY);
-                self.board.display(draw, &rect);
+                let replay = self.replayed(time);
+                let board = replay.as_ref().unwrap_or(&self.board);
+                board_display::show_grid(draw, &board.grid, rect);
+                board_display::display(draw, board, rect, &self.timeline, time);
+                if let Some(remote) = &self.remote {
+                    let status = format!("{}, {}", remote.status(&self.board), self.news);
+                    let location = Rect::from_x_y_w_h(0.0, rect.bottom() + 20.0, rect.w(), 30.0);
+                    let st = text(&status).font_size(16).build(location);
+                    draw.path().fill().color(BLACK).events(st.path_events());
+                }
             }
         }
+        if let Some(start) = self.fade {
+            // The background colour covers the game, then uncovers the menu.
+            let cover = Tween::new(start, FADE / 2.0).ease(Ease::InOutQuad);
+            let alpha = cover.progress(time) - cover.then(FADE / 2.0).progress(time);
+            draw.rect()
+                .x_y(rect.x(), rect.y())
+                .w_h(rect.w(), rect.h())
+                .color(rgba(0.53, 0.81, 0.92, alpha));
+        }
     }
 }
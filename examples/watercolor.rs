@@ -1,15 +1,104 @@
 extern crate nannou;
+use canvas::Canvas;
 use nannou::prelude::*;
+use painter::{Painter, Raster};
+use params::{cli, Panel, Params, Sheet};
+use std::path::Path;
+use watercolor::WatercolorBrush;
 
 const WIDTH: f32 = 1024.;
 const HEIGHT: f32 = 800.;
+/// Thumbnail width of saved contact sheets.
+const THUMB: f32 = 256.;
 
 fn main() {
+    if let Some(file) = cli::sheet_target() {
+        let sheet = Sheet::from_args().unwrap_or_else(|| Sheet::new(4, 3));
+        save_sheet(&sheet, &params(), file);
+        return;
+    }
+    if let Some(file) = cli::render_target() {
+        save_still(&params(), file);
+        return;
+    }
     nannou::app(model).update(update).run();
 }
 
 struct Model {
     _window: window::Id,
+    brush: WatercolorBrush,
+    // The brush rendered with its mask, redrawn when the brush changes.
+    canvas: Canvas,
+    // Layer opacity the canvas was rendered with.
+    opacity: f32,
+    panel: Panel<Model>,
+    // Contact sheet shown instead of the brush, see `Key::G`.
+    sheet: Option<(Sheet, Vec<Params>, Vec<WatercolorBrush>)>,
+}
+
+impl Model {
+    fn regenerate(&mut self) {
+        self.brush = brush(&self.panel.params);
+        self.redraw();
+    }
+
+    fn redraw(&mut self) {
+        self.canvas.clear();
+        self.brush.render(&mut self.canvas);
+    }
+}
+
+fn params() -> Params {
+    Params::new()
+        .int("sides", 10, 3..=24)
+        .int("layers", 40, 1..=100)
+        .float("opacity", 0.04, 0.01..=0.2)
+        .args()
+}
+
+fn brush(params: &Params) -> WatercolorBrush {
+    watercolor::seed(params.seed());
+    let r = WIDTH.min(HEIGHT) / 3.;
+    let poly = watercolor::rpoly(r, params.i64("sides") as u32);
+    let mask = watercolor::texture(WIDTH as u32, HEIGHT as u32, 400, 40.);
+    WatercolorBrush::new(&poly, rgba(0.5, 0., 0., 1.))
+        .layers(params.usize("layers"))
+        .opacity(params.f32("opacity"))
+        .mask(mask)
+}
+
+fn sheet_cells(sheet: &Sheet, params: &Params) -> (Vec<Params>, Vec<WatercolorBrush>) {
+    let cells = sheet.cells(params).unwrap_or_else(|err| {
+        eprintln!("{}", err);
+        Vec::new()
+    });
+    let brushes = cells.iter().map(brush).collect();
+    (cells, brushes)
+}
+
+fn save_sheet<P: AsRef<Path>>(sheet: &Sheet, params: &Params, file: P) {
+    let (cells, brushes) = sheet_cells(sheet, params);
+    let source = Rect::from_w_h(WIDTH, HEIGHT);
+    let (w, h) = sheet.size(source, THUMB);
+    let raster = Raster::new(w, h);
+    raster.background(rgba(1., 1., 1., 1.));
+    let area = Rect::from_w_h(w as f32, h as f32);
+    sheet.paint(&raster, area, source, &cells, |painter, i| {
+        brushes[i].paint(painter)
+    });
+    raster.save(&file).unwrap();
+    params.save_beside(&file).unwrap();
+}
+
+fn blank() -> Canvas {
+    Canvas::with_background(WIDTH as u32, HEIGHT as u32, rgba(1., 1., 1., 1.))
+}
+
+fn save_still<P: AsRef<Path>>(params: &Params, file: P) {
+    let mut canvas = blank();
+    brush(params).render(&mut canvas);
+    canvas.save(&file).unwrap();
+    params.save_beside(&file).unwrap();
 }
 
 fn model(app: &App) -> Model {
@@ -22,16 +111,77 @@ fn model(app: &App) -> Model {
         .build()
         .unwrap();
 
-    Model { _window }
+    let params = params();
+    let brush = brush(&params);
+    // Only the shape needs new layers, opacity is read every update.
+    let opacity = params.f32("opacity");
+    let panel =
+        Panel::new(app, _window, params).on_change(&["sides", "layers", "seed"], Model::regenerate);
+
+    let mut model = Model {
+        _window,
+        brush,
+        canvas: blank(),
+        opacity,
+        panel,
+        sheet: None,
+    };
+    model.redraw();
+    model
 }
 
-fn update(_app: &App, _model: &mut Model, _update: Update) {}
+fn update(_app: &App, model: &mut Model, _update: Update) {
+    Panel::update(model, |model| &mut model.panel);
+    let opacity = model.panel.params.f32("opacity");
+    if opacity != model.opacity {
+        model.opacity = opacity;
+        model.brush.set_opacity(opacity);
+        model.redraw();
+    }
+}
 
-fn window_event(_app: &App, _model: &mut Model, event: WindowEvent) {
+fn window_event(app: &App, model: &mut Model, event: WindowEvent) {
     match event {
-        KeyPressed(_key) => {}
+        KeyPressed(key) => match key {
+            Key::R => {
+                model.brush.regenerate();
+                model.redraw();
+            }
+            Key::N => model.panel.params.reseed(),
+            Key::P => model.panel.toggle(),
+            Key::G => {
+                model.sheet = match model.sheet.take() {
+                    Some(_) => None,
+                    None => {
+                        let sheet = Sheet::from_args().unwrap_or_else(|| Sheet::new(4, 3));
+                        let (cells, brushes) = sheet_cells(&sheet, &model.panel.params);
+                        Some((sheet, cells, brushes))
+                    }
+                }
+            }
+            Key::S => match &model.sheet {
+                Some((sheet, ..)) => save_sheet(sheet, &model.panel.params, "watercolor_sheet.png"),
+                None => save_still(&model.panel.params, "watercolor.png"),
+            },
+            _ => {}
+        },
         KeyReleased(_key) => {}
         MouseMoved(_pos) => {}
+        // Open the clicked cell of the contact sheet full size.
+        MousePressed(MouseButton::Left) => {
+            let clicked = model.sheet.as_ref().and_then(|(sheet, cells, _)| {
+                let i = sheet.cell_at(app.window_rect(), app.mouse.position())?;
+                Some(cells[i].clone())
+            });
+            if let Some(cell) = clicked {
+                let params = &mut model.panel.params;
+                params.set_seed(cell.seed());
+                for param in cell.iter() {
+                    params.set(&param.name, param.value()).unwrap();
+                }
+                model.sheet = None;
+            }
+        }
         MousePressed(_button) => {}
         MouseReleased(_button) => {}
         MouseEntered => {}
@@ -50,94 +200,24 @@ fn window_event(_app: &App, _model: &mut Model, event: WindowEvent) {
     }
 }
 
-fn view(app: &App, _model: &Model, frame: &Frame) {
+fn view(app: &App, model: &Model, frame: &Frame) {
     // Prepare to draw.
     let draw = app.draw();
-    // Clear the background to pink.
-    draw.background().color(BLACK);
-    // Draw a red ellipse with default size and position.
-
-    let win = app.window_rect();
-    let _t = app.time;
-
-    let r = win.w().min(win.h()) / 2.;
-    let base_poly = create_base_poly(r, 10);
-    let variation = deform(&base_poly, 5, random_range(r / 10., r / 4.), 4.);
-    draw.polygon().join_round().color(MAROON).points(variation);
-
-    for poly in polystack(r, 10) {
-        draw.polygon().join_round().color(MAROON).points(poly);
+    draw.background(rgba(1., 1., 1., 1.));
+
+    match &model.sheet {
+        Some((sheet, cells, brushes)) => {
+            let source = Rect::from_w_h(WIDTH, HEIGHT);
+            sheet.paint(&draw, app.window_rect(), source, cells, |painter, i| {
+                brushes[i].paint(painter)
+            });
+        }
+        None => {
+            draw.texture(&model.canvas.to_texture(app))
+                .w_h(WIDTH, HEIGHT);
+        }
     }
 
     draw.to_frame(app, &frame).unwrap();
-}
-
-fn polystack(r: f32, nsides: u32) -> Vec<Vec<Point2>> {
-    let mut stack = Vec::new();
-
-    /* Generate a base polygon with depth 5 and variance 15 */
-    let base_poly = rpoly(r, nsides);
-    let base_poly = deform(&base_poly, 5, r / 10., 2.);
-
-    /* Generate a variation of the base polygon with a random variance */
-    for _ in 0..5 {
-        let poly = deform(&base_poly, 5, random_range(r / 15., r / 5.), 4.);
-        stack.push(poly);
-    }
-
-    stack
-}
-
-fn create_base_poly(r: f32, nsides: u32) -> Vec<Point2> {
-    deform(&rpoly(r, nsides), 5, r / 2., 2.0)
-}
-
-fn rpoly(radius: f32, n_points: u32) -> Vec<Point2> {
-    (0..n_points)
-        .map(|i| {
-            let fract = i as f32 / n_points as f32;
-            let phase = fract;
-            let x = radius * (TAU * phase).cos();
-            let y = radius * (TAU * phase).sin();
-            pt2(x, y)
-        })
-        .collect()
-}
-
-fn deform(points: &Vec<Point2>, depth: i64, variance: f32, vdiv: f32) -> Vec<Point2> {
-    let mut new_points = Vec::new();
-
-    for i in 0..points.len() {
-        let sx1 = points[i].x;
-        let sy1 = points[i].y;
-        let sx2 = points[(i + 1) % points.len()].x;
-        let sy2 = points[(i + 1) % points.len()].y;
-
-        new_points.push(pt2(sx1, sy1));
-        sub_divide(&mut new_points, sx1, sy1, sx2, sy2, depth, variance, vdiv);
-    }
-
-    new_points
-}
-
-fn sub_divide(
-    new_points: &mut Vec<Point2>,
-    x1: f32,
-    y1: f32,
-    x2: f32,
-    y2: f32,
-    depth: i64,
-    variance: f32,
-    vdiv: f32,
-) {
-    if depth >= 0 {
-        let midx = (x1 + x2) / 2.;
-        let midy = (y1 + y2) / 2.;
-        let nx = midx + random_range(-0.5, 0.5) * variance;
-        let ny = midy + random_range(-0.5, 0.5) * variance;
-
-        sub_divide(new_points, x1, y1, nx, ny, depth - 1, variance / vdiv, vdiv);
-        new_points.push(pt2(nx, ny));
-        sub_divide(new_points, nx, ny, x2, y2, depth - 1, variance / vdiv, vdiv);
-    }
+    model.panel.draw(app, &frame);
 }
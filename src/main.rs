@@ -1,5 +1,315 @@
-extern crate nannou;
+//! Launcher for the sketches in `examples/`.
+//!
+//! Sketches stay separate binaries, this finds them, builds them with cargo
+//! and hands the flags through. Sketches with a parameter panel read
+//! `--seed N`, `--preset FILE` and `--set name=value` themselves, the flags
+//! in `ONLY_FOR` are refused for sketches that would ignore them. Only the
+//! ones in `RENDERABLE` can draw a still without a window with
+//! `--render FILE`, `render` refuses the rest.
+use std::env;
+use std::fs;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::process::{self, Command};
+
+const USAGE: &str = "usage:
+    prosessing list
+    prosessing run <sketch> [--seed N] [--preset FILE] [--set name=value]...
+    prosessing render <sketch> [--seeds A..B] [--format png|svg] [--out DIR] [flags of run]...
+
+--size WxH works for line_cliping only, --sheet FILE --grid CxR for
+watercolor only. render works for line_cliping (png, svg) and
+watercolor (png).";
+
+/// Flags only some sketches read, with the sketches that read them.
+const ONLY_FOR: &[(&str, &[&str])] = &[
+    ("--size", &["line_cliping"]),
+    ("--sheet", &["watercolor"]),
+    ("--grid", &["watercolor"]),
+];
+
+/// Sketches that handle `--render FILE` and the formats they can write.
+/// Anything else would open a window and never return.
+const RENDERABLE: &[(&str, &[&str])] =
+    &[("line_cliping", &["png", "svg"]), ("watercolor", &["png"])];
 
 fn main() {
-    println!("Hello, World!");
+    let args: Vec<String> = env::args().skip(1).collect();
+    let result = match args.first().map(String::as_str) {
+        Some("list") => list(),
+        Some("run") if args.len() > 1 => run(&args[1], &args[2..]),
+        Some("render") if args.len() > 1 => render(&args[1], &args[2..]),
+        _ => {
+            eprintln!("{}", USAGE);
+            process::exit(2);
+        }
+    };
+    if let Err(err) = result {
+        eprintln!("{}", err);
+        process::exit(1);
+    }
+}
+
+fn root() -> &'static Path {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+}
+
+/// Every example cargo knows about, single files and directories with a
+/// `main.rs`.
+fn sketches() -> Vec<String> {
+    let entries = match fs::read_dir(root().join("examples")) {
+        Ok(entries) => entries,
+        Err(_) => return Vec::new(),
+    };
+    let mut names: Vec<String> = entries
+        .filter_map(|entry| {
+            let path = entry.ok()?.path();
+            let sketch = path.extension().and_then(|ext| ext.to_str()) == Some("rs")
+                || path.join("main.rs").is_file();
+            if sketch {
+                Some(path.file_stem()?.to_str()?.to_string())
+            } else {
+                None
+            }
+        })
+        .collect();
+    names.sort();
+    names
+}
+
+fn find(name: &str) -> Result<(), String> {
+    if sketches().iter().any(|sketch| sketch == name) {
+        Ok(())
+    } else {
+        Err(format!("no sketch `{}`, see `prosessing list`", name))
+    }
+}
+
+fn list() -> Result<(), String> {
+    for name in sketches() {
+        println!("{}", name);
+    }
+    Ok(())
+}
+
+fn cargo(command: &str, name: &str) -> Command {
+    let mut cargo = Command::new(env::var("CARGO").unwrap_or_else(|_| "cargo".to_string()));
+    cargo
+        .current_dir(root())
+        .arg(command)
+        .arg("--release")
+        .arg("--example")
+        .arg(name);
+    cargo
+}
+
+fn run(name: &str, flags: &[String]) -> Result<(), String> {
+    find(name)?;
+    supported(name, flags)?;
+    let status = cargo("run", name)
+        .arg("--")
+        .args(flags)
+        .status()
+        .map_err(|err| format!("can't start cargo: {}", err))?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(format!("{} exited with {}", name, status))
+    }
+}
+
+/// Render one still per seed. The sketch is built once and its binary run
+/// directly, a few hundred seeds shouldn't pay for cargo every time.
+fn render(name: &str, args: &[String]) -> Result<(), String> {
+    find(name)?;
+    let options = RenderOptions::parse(args)?;
+    renderable(name, &options.format)?;
+    supported(name, &options.flags)?;
+
+    let status = cargo("build", name)
+        .status()
+        .map_err(|err| format!("can't start cargo: {}", err))?;
+    if !status.success() {
+        return Err(format!("failed to build {}", name));
+    }
+    let target = env::var_os("CARGO_TARGET_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| root().join("target"));
+    let binary = target.join("release").join("examples").join(format!(
+        "{}{}",
+        name,
+        env::consts::EXE_SUFFIX
+    ));
+    fs::create_dir_all(&options.out).map_err(|err| err.to_string())?;
+
+    let (mut rendered, total) = (0, options.seeds.end - options.seeds.start);
+    for seed in options.seeds.clone() {
+        let file = options
+            .out
+            .join(format!("{}_{:05}.{}", name, seed, options.format));
+        let status = Command::new(&binary)
+            .args(&options.flags)
+            .arg("--seed")
+            .arg(seed.to_string())
+            .arg("--render")
+            .arg(&file)
+            .status()
+            .map_err(|err| format!("can't start {}: {}", binary.display(), err))?;
+        if status.success() && file.exists() {
+            rendered += 1;
+            println!("{}", file.display());
+        } else if rendered == 0 {
+            return Err(format!("{} failed on seed {}", name, seed));
+        } else {
+            eprintln!("seed {} failed", seed);
+        }
+    }
+    println!(
+        "rendered {} of {} into {}",
+        rendered,
+        total,
+        options.out.display()
+    );
+    Ok(())
+}
+
+/// Refuse flags from `ONLY_FOR` that `name` would silently ignore.
+fn supported(name: &str, flags: &[String]) -> Result<(), String> {
+    for (flag, sketches) in ONLY_FOR {
+        if flags.iter().any(|f| f == flag) && !sketches.contains(&name) {
+            return Err(format!("{} doesn't take {}, see usage", name, flag));
+        }
+    }
+    Ok(())
+}
+
+fn renderable(name: &str, format: &str) -> Result<(), String> {
+    let formats = RENDERABLE
+        .iter()
+        .find(|(sketch, _)| *sketch == name)
+        .map(|(_, formats)| *formats)
+        .ok_or_else(|| format!("{} can't render stills, only runs in a window", name))?;
+    if formats.contains(&format) {
+        Ok(())
+    } else {
+        Err(format!("{} can't render to {}", name, format))
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct RenderOptions {
+    seeds: Range<u64>,
+    format: String,
+    out: PathBuf,
+    /// Everything else, passed on to the sketch.
+    flags: Vec<String>,
+}
+
+impl RenderOptions {
+    fn parse(args: &[String]) -> Result<Self, String> {
+        let mut options = RenderOptions {
+            seeds: 0..10,
+            format: "png".to_string(),
+            out: PathBuf::from("renders"),
+            flags: Vec::new(),
+        };
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            let mut value = || {
+                args.next()
+                    .cloned()
+                    .ok_or_else(|| format!("{} needs a value", arg))
+            };
+            match arg.as_str() {
+                "--seeds" => options.seeds = parse_seeds(&value()?)?,
+                "--format" => options.format = value()?,
+                "--out" => options.out = PathBuf::from(value()?),
+                "--seed" | "--render" => return Err(format!("{} is set per render", arg)),
+                _ => options.flags.push(arg.clone()),
+            }
+        }
+        if options.format != "png" && options.format != "svg" {
+            return Err(format!(
+                "can't render to {}, only png or svg",
+                options.format
+            ));
+        }
+        Ok(options)
+    }
+}
+
+/// `A..B`, `A..=B` or a single seed.
+fn parse_seeds(seeds: &str) -> Result<Range<u64>, String> {
+    let number = |s: &str| {
+        s.trim()
+            .parse::<u64>()
+            .map_err(|_| format!("not a seed range: {}", seeds))
+    };
+    let range = if let Some(i) = seeds.find("..=") {
+        number(&seeds[..i])?..number(&seeds[i + 3..])? + 1
+    } else if let Some(i) = seeds.find("..") {
+        number(&seeds[..i])?..number(&seeds[i + 2..])?
+    } else {
+        let seed = number(seeds)?;
+        seed..seed + 1
+    };
+    if range.start >= range.end {
+        return Err(format!("empty seed range: {}", seeds));
+    }
+    Ok(range)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn args(args: &str) -> Vec<String> {
+        args.split_whitespace().map(String::from).collect()
+    }
+
+    #[test]
+    fn parses_seed_ranges() {
+        assert_eq!(parse_seeds("0..100"), Ok(0..100));
+        assert_eq!(parse_seeds("3..=5"), Ok(3..6));
+        assert_eq!(parse_seeds("42"), Ok(42..43));
+        assert!(parse_seeds("5..5").is_err());
+        assert!(parse_seeds("a..b").is_err());
+    }
+
+    #[test]
+    fn render_flags_are_split() {
+        let options =
+            RenderOptions::parse(&args("--size 512x512 --seeds 1..3 --format svg --set a=1"))
+                .unwrap();
+        assert_eq!(options.seeds, 1..3);
+        assert_eq!(options.format, "svg");
+        assert_eq!(options.flags, args("--size 512x512 --set a=1"));
+        assert!(RenderOptions::parse(&args("--format jpg")).is_err());
+        assert!(RenderOptions::parse(&args("--seed 3")).is_err());
+    }
+
+    #[test]
+    fn only_listed_sketches_render() {
+        assert!(renderable("line_cliping", "svg").is_ok());
+        assert!(renderable("watercolor", "png").is_ok());
+        assert!(renderable("watercolor", "svg").is_err());
+        assert!(renderable("pendulums", "png").is_err());
+        for (name, _) in RENDERABLE {
+            assert!(sketches().iter().any(|sketch| sketch == name), "{}", name);
+        }
+    }
+
+    #[test]
+    fn sketch_specific_flags_are_checked() {
+        assert!(supported("line_cliping", &args("--size 512x512 --seed 3")).is_ok());
+        assert!(supported("watercolor", &args("--size 512x512")).is_err());
+        assert!(supported("watercolor", &args("--sheet s.png --grid 4x3")).is_ok());
+        assert!(supported("pendulums", &args("--grid 4x3")).is_err());
+        assert!(supported("pendulums", &args("--set joints=3")).is_ok());
+        for (_, names) in ONLY_FOR {
+            for name in names.iter() {
+                assert!(sketches().iter().any(|sketch| sketch == name), "{}", name);
+            }
+        }
+    }
 }
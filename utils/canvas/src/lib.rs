@@ -0,0 +1,252 @@
+use image::{Rgba, RgbaImage};
+use nannou::prelude::*;
+use std::path::Path;
+
+pub mod raster;
+
+/// How a new color is combined with what is already on the canvas.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlendMode {
+    Normal,
+    Add,
+    Multiply,
+    Screen,
+    Lighten,
+    Darken,
+}
+
+impl BlendMode {
+    fn apply(self, src: f32, dst: f32) -> f32 {
+        match self {
+            BlendMode::Normal => src,
+            BlendMode::Add => (src + dst).min(1.),
+            BlendMode::Multiply => src * dst,
+            BlendMode::Screen => 1. - (1. - src) * (1. - dst),
+            BlendMode::Lighten => src.max(dst),
+            BlendMode::Darken => src.min(dst),
+        }
+    }
+}
+
+/// CPU-side accumulation buffer.
+///
+/// Nothing but `to_texture` touches the GPU, so a canvas can live across
+/// frames of a window or be driven from a plain loop and saved at the end.
+/// Drawing methods take points in sketch coordinates: origin in the middle,
+/// y up.
+pub struct Canvas {
+    width: u32,
+    height: u32,
+    pixels: Vec<[f32; 4]>,
+    background: [f32; 4],
+    blend: BlendMode,
+}
+
+impl Canvas {
+    pub fn new(width: u32, height: u32) -> Self {
+        Self::with_background(width, height, srgba(0., 0., 0., 0.))
+    }
+
+    pub fn with_background(width: u32, height: u32, background: Srgba) -> Self {
+        let background = components(background);
+        Canvas {
+            width,
+            height,
+            pixels: vec![background; (width * height) as usize],
+            background,
+            blend: BlendMode::Normal,
+        }
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Blend mode used by every following drawing call.
+    pub fn set_blend_mode(&mut self, blend: BlendMode) {
+        self.blend = blend;
+    }
+
+    pub fn blend_mode(&self) -> BlendMode {
+        self.blend
+    }
+
+    /// Background used by `clear` and `fade` from now on.
+    pub fn set_background(&mut self, background: Srgba) {
+        self.background = components(background);
+    }
+
+    /// Reset every pixel to the background color.
+    pub fn clear(&mut self) {
+        let background = self.background;
+        self.pixels.iter_mut().for_each(|p| *p = background);
+    }
+
+    /// Pull every pixel towards the background by `amount` in `0..1`.
+    ///
+    /// Called once per frame this gives trails that fade out without
+    /// having to remember what was drawn.
+    pub fn fade(&mut self, amount: f32) {
+        let amount = amount.clamp(0., 1.);
+        let background = self.background;
+        for p in &mut self.pixels {
+            for i in 0..4 {
+                p[i] += (background[i] - p[i]) * amount;
+            }
+        }
+    }
+
+    /// Sketch coordinates to pixel coordinates.
+    pub fn to_pixel(&self, p: Point2) -> Point2 {
+        pt2(p.x + self.width as f32 / 2., self.height as f32 / 2. - p.y)
+    }
+
+    /// Blend `color` into a single pixel, `coverage` scales its alpha.
+    pub fn blend_pixel(&mut self, x: u32, y: u32, color: Srgba, coverage: f32) {
+        if x >= self.width || y >= self.height {
+            return;
+        }
+        let src = components(color);
+        let sa = src[3] * coverage;
+        if sa <= 0. {
+            return;
+        }
+        let dst = &mut self.pixels[(y * self.width + x) as usize];
+        let da = dst[3];
+        let out_a = sa + da * (1. - sa);
+        for i in 0..3 {
+            let mixed = self.blend.apply(src[i], dst[i]);
+            let c = sa * (1. - da) * src[i] + sa * da * mixed + (1. - sa) * da * dst[i];
+            dst[i] = if out_a > 0. { c / out_a } else { 0. };
+        }
+        dst[3] = out_a;
+    }
+
+    pub fn point(&mut self, p: Point2, color: Srgba) {
+        let p = self.to_pixel(p);
+        if p.x >= 0. && p.y >= 0. {
+            self.blend_pixel(p.x as u32, p.y as u32, color, 1.);
+        }
+    }
+
+    pub fn line(&mut self, a: Point2, b: Point2, weight: f32, color: Srgba) {
+        let outline = raster::stroke_segment(self.to_pixel(a), self.to_pixel(b), weight);
+        self.fill_pixels(&outline, color);
+    }
+
+    pub fn polyline(&mut self, points: &[Point2], weight: f32, color: Srgba) {
+        let points: Vec<Point2> = points.iter().map(|&p| self.to_pixel(p)).collect();
+        let outline = raster::stroke_polyline(&points, weight);
+        self.fill_pixels(&outline, color);
+    }
+
+    pub fn polygon(&mut self, points: &[Point2], color: Srgba) {
+        let points: Vec<Point2> = points.iter().map(|&p| self.to_pixel(p)).collect();
+        self.fill_pixels(&points, color);
+    }
+
+    pub fn ellipse(&mut self, center: Point2, w: f32, h: f32, color: Srgba) {
+        let outline = raster::ellipse(self.to_pixel(center), w / 2., h / 2.);
+        self.fill_pixels(&outline, color);
+    }
+
+    /// Fill a polygon already in pixel coordinates.
+    pub fn fill_pixels(&mut self, points: &[Point2], color: Srgba) {
+        let (w, h) = (self.width, self.height);
+        raster::fill_polygon(points, w, h, |x, y, c| self.blend_pixel(x, y, color, c));
+    }
+
+    pub fn to_image(&self) -> RgbaImage {
+        RgbaImage::from_fn(self.width, self.height, |x, y| {
+            let p = self.pixels[(y * self.width + x) as usize];
+            let c = |v: f32| (v.clamp(0., 1.) * 255.).round() as u8;
+            Rgba([c(p[0]), c(p[1]), c(p[2]), c(p[3])])
+        })
+    }
+
+    /// Upload the canvas to show it in a window with `draw.texture`, the
+    /// only place it meets the GPU. Meant to be called once per frame.
+    pub fn to_texture(&self, app: &App) -> wgpu::Texture {
+        // Through nannou's own `image`, which may be a different version.
+        let raw = self.to_image().into_raw();
+        let image = nannou::image::RgbaImage::from_raw(self.width, self.height, raw).unwrap();
+        wgpu::Texture::from_image(app, &nannou::image::DynamicImage::ImageRgba8(image))
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> std::io::Result<()> {
+        self.to_image().save(path)
+    }
+}
+
+fn components(color: Srgba) -> [f32; 4] {
+    [color.red, color.green, color.blue, color.alpha]
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const DST: [f32; 3] = [0.5, 0.2, 0.8];
+    const SRC: [f32; 3] = [0.6, 0.4, 0.1];
+
+    /// A single opaque `DST` pixel with `SRC` blended over it at `alpha`.
+    fn blended(blend: BlendMode, alpha: f32) -> [f32; 4] {
+        let mut canvas = Canvas::with_background(1, 1, srgba(DST[0], DST[1], DST[2], 1.));
+        canvas.set_blend_mode(blend);
+        canvas.blend_pixel(0, 0, srgba(SRC[0], SRC[1], SRC[2], alpha), 1.);
+        canvas.pixels[0]
+    }
+
+    fn assert_close(pixel: [f32; 4], expected: [f32; 4]) {
+        for (got, want) in pixel.iter().zip(&expected) {
+            assert!((got - want).abs() < 1e-5, "{:?} != {:?}", pixel, expected);
+        }
+    }
+
+    #[test]
+    fn blend_modes_mix_channels() {
+        let cases = [
+            (BlendMode::Normal, [0.6, 0.4, 0.1]),
+            (BlendMode::Add, [1.0, 0.6, 0.9]),
+            (BlendMode::Multiply, [0.3, 0.08, 0.08]),
+            (BlendMode::Screen, [0.8, 0.52, 0.82]),
+            (BlendMode::Lighten, [0.6, 0.4, 0.8]),
+            (BlendMode::Darken, [0.5, 0.2, 0.1]),
+        ];
+        for &(blend, [r, g, b]) in cases.iter() {
+            assert_close(blended(blend, 1.), [r, g, b, 1.]);
+        }
+        // Half covered, half of the way from what was there.
+        assert_close(blended(BlendMode::Normal, 0.5), [0.55, 0.3, 0.45, 1.]);
+        assert_close(blended(BlendMode::Multiply, 0.5), [0.4, 0.14, 0.44, 1.]);
+    }
+
+    #[test]
+    fn paint_over_transparent_keeps_its_color() {
+        let mut canvas = Canvas::new(1, 1);
+        canvas.set_blend_mode(BlendMode::Multiply);
+        canvas.blend_pixel(0, 0, srgba(SRC[0], SRC[1], SRC[2], 0.5), 1.);
+        assert_close(canvas.pixels[0], [0.6, 0.4, 0.1, 0.5]);
+        canvas.blend_pixel(1, 0, srgba(1., 1., 1., 1.), 1.);
+        assert_close(canvas.pixels[0], [0.6, 0.4, 0.1, 0.5]);
+    }
+
+    #[test]
+    fn fade_and_clear_return_to_background() {
+        let mut canvas = Canvas::with_background(2, 1, srgba(0.2, 0.2, 0.2, 1.));
+        canvas.blend_pixel(0, 0, srgba(1., 0.6, 0., 1.), 1.);
+        canvas.fade(0.25);
+        assert_close(canvas.pixels[0], [0.8, 0.5, 0.05, 1.]);
+        assert_close(canvas.pixels[1], [0.2, 0.2, 0.2, 1.]);
+        canvas.fade(2.);
+        assert_close(canvas.pixels[0], [0.2, 0.2, 0.2, 1.]);
+
+        canvas.blend_pixel(1, 0, srgba(1., 1., 1., 1.), 1.);
+        canvas.clear();
+        assert_close(canvas.pixels[1], [0.2, 0.2, 0.2, 1.]);
+    }
+}
@@ -0,0 +1,211 @@
+//! Anti-aliased scanline rasterisation into a coverage callback.
+//!
+//! Everything here works in pixel space: origin in the top left corner,
+//! y pointing down, pixel centers at half coordinates.
+use nannou::prelude::*;
+
+/// Sub-scanlines per pixel row used for vertical anti-aliasing.
+const SUBSAMPLES: usize = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FillRule {
+    NonZero,
+    EvenOdd,
+}
+
+struct Edge {
+    x0: f32,
+    y0: f32,
+    x1: f32,
+    y1: f32,
+    winding: i32,
+}
+
+/// Fill a single closed polygon with the non-zero rule.
+pub fn fill_polygon<F>(points: &[Point2], width: u32, height: u32, plot: F)
+where
+    F: FnMut(u32, u32, f32),
+{
+    fill(&[points], FillRule::NonZero, width, height, plot)
+}
+
+/// Fill a set of closed contours, calling `plot(x, y, coverage)` for
+/// every pixel with non-zero coverage.
+pub fn fill<F>(contours: &[&[Point2]], rule: FillRule, width: u32, height: u32, mut plot: F)
+where
+    F: FnMut(u32, u32, f32),
+{
+    let mut edges = Vec::new();
+    for contour in contours {
+        for i in 0..contour.len() {
+            let (a, b) = (contour[i], contour[(i + 1) % contour.len()]);
+            if a.y == b.y {
+                continue;
+            }
+            let edge = if a.y < b.y {
+                Edge {
+                    x0: a.x,
+                    y0: a.y,
+                    x1: b.x,
+                    y1: b.y,
+                    winding: 1,
+                }
+            } else {
+                Edge {
+                    x0: b.x,
+                    y0: b.y,
+                    x1: a.x,
+                    y1: a.y,
+                    winding: -1,
+                }
+            };
+            edges.push(edge);
+        }
+    }
+    if edges.is_empty() || width == 0 || height == 0 {
+        return;
+    }
+
+    let ymin = edges.iter().map(|e| e.y0).fold(f32::MAX, f32::min);
+    let ymax = edges.iter().map(|e| e.y1).fold(f32::MIN, f32::max);
+    let row_start = ymin.floor().max(0.) as u32;
+    let row_end = (ymax.ceil().max(0.) as u32).min(height);
+
+    let weight = 1. / SUBSAMPLES as f32;
+    let mut coverage = vec![0f32; width as usize + 1];
+    let mut crossings: Vec<(f32, i32)> = Vec::new();
+    for row in row_start..row_end {
+        let mut touched = (usize::MAX, 0);
+        for s in 0..SUBSAMPLES {
+            let sy = row as f32 + (s as f32 + 0.5) * weight;
+            crossings.clear();
+            for e in &edges {
+                if sy >= e.y0 && sy < e.y1 {
+                    let x = e.x0 + (sy - e.y0) / (e.y1 - e.y0) * (e.x1 - e.x0);
+                    crossings.push((x, e.winding));
+                }
+            }
+            crossings.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+
+            let mut winding = 0;
+            for pair in crossings.windows(2) {
+                winding += pair[0].1;
+                let inside = match rule {
+                    FillRule::NonZero => winding != 0,
+                    FillRule::EvenOdd => winding % 2 != 0,
+                };
+                if inside {
+                    let span = add_span(&mut coverage, pair[0].0, pair[1].0, width, weight);
+                    if let Some((lo, hi)) = span {
+                        touched = (touched.0.min(lo), touched.1.max(hi));
+                    }
+                }
+            }
+        }
+        if touched.0 > touched.1 {
+            continue;
+        }
+        let last = touched.1.min(width as usize - 1);
+        for (x, c) in coverage.iter_mut().enumerate().take(last + 1).skip(touched.0) {
+            if *c > 0. {
+                plot(x as u32, row, c.min(1.));
+            }
+            *c = 0.;
+        }
+        coverage[width as usize] = 0.;
+    }
+}
+
+/// Accumulate exact horizontal coverage of `[xa, xb)` into `coverage`.
+fn add_span(
+    coverage: &mut [f32],
+    xa: f32,
+    xb: f32,
+    width: u32,
+    weight: f32,
+) -> Option<(usize, usize)> {
+    let xa = xa.max(0.);
+    let xb = xb.min(width as f32);
+    if xb <= xa {
+        return None;
+    }
+    let ia = xa.floor() as usize;
+    let ib = xb.floor() as usize;
+    if ia == ib {
+        coverage[ia] += (xb - xa) * weight;
+    } else {
+        coverage[ia] += (ia as f32 + 1. - xa) * weight;
+        for c in &mut coverage[ia + 1..ib] {
+            *c += weight;
+        }
+        coverage[ib] += (xb - ib as f32) * weight;
+    }
+    Some((ia, ib))
+}
+
+/// Outline of a line segment `weight` wide with butt caps.
+pub fn stroke_segment(a: Point2, b: Point2, weight: f32) -> Vec<Point2> {
+    let d = b - a;
+    if d.magnitude2() == 0. {
+        return Vec::new();
+    }
+    let d = d.normalize() * (weight / 2.);
+    let n = vec2(-d.y, d.x);
+    vec![a + n, b + n, b - n, a - n]
+}
+
+/// Outline of an open polyline `weight` wide with miter joins.
+pub fn stroke_polyline(points: &[Point2], weight: f32) -> Vec<Point2> {
+    let left = polyline::offset(points, weight / 2., polyline::Join::Miter(4.));
+    let right = polyline::offset(points, -weight / 2., polyline::Join::Miter(4.));
+    left.into_iter().chain(right.into_iter().rev()).collect()
+}
+
+/// Polygon approximating an axis aligned ellipse.
+pub fn ellipse(center: Point2, radius_x: f32, radius_y: f32) -> Vec<Point2> {
+    let r = radius_x.abs().max(radius_y.abs());
+    let segments = ((r.sqrt() * 8.) as usize).max(12);
+    (0..segments)
+        .map(|i| {
+            let a = TAU * i as f32 / segments as f32;
+            center + vec2(radius_x * a.cos(), radius_y * a.sin())
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn area(points: &[Point2], width: u32, height: u32) -> f32 {
+        let mut sum = 0.;
+        fill_polygon(points, width, height, |_, _, c| sum += c);
+        sum
+    }
+
+    #[test]
+    fn coverage_matches_area() {
+        let square = [pt2(2.5, 2.5), pt2(7.5, 2.5), pt2(7.5, 7.5), pt2(2.5, 7.5)];
+        assert!((area(&square, 10, 10) - 25.).abs() < 1e-3);
+        let triangle = [pt2(0., 0.), pt2(8., 0.), pt2(0., 8.)];
+        assert!((area(&triangle, 10, 10) - 32.).abs() < 0.5);
+    }
+
+    #[test]
+    fn clipped_to_bounds() {
+        let square = [pt2(-5., -5.), pt2(5., -5.), pt2(5., 5.), pt2(-5., 5.)];
+        assert!((area(&square, 10, 10) - 25.).abs() < 1e-3);
+        let mut pixels = Vec::new();
+        fill_polygon(&square, 3, 3, |x, y, _| pixels.push((x, y)));
+        assert_eq!(pixels.len(), 9);
+    }
+
+    #[test]
+    fn even_odd_leaves_hole() {
+        let outer = [pt2(0., 0.), pt2(6., 0.), pt2(6., 6.), pt2(0., 6.)];
+        let inner = [pt2(2., 2.), pt2(4., 2.), pt2(4., 4.), pt2(2., 4.)];
+        let mut sum = 0.;
+        fill(&[&outer, &inner], FillRule::EvenOdd, 10, 10, |_, _, c| sum += c);
+        assert!((sum - 32.).abs() < 1e-3);
+    }
+}
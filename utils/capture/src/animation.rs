@@ -0,0 +1,438 @@
+//! Animated GIF and APNG output, plus finding where an animation loops.
+use gif::SetParameter;
+use image::imageops::colorops::ColorMap;
+use image::imageops::{self, FilterType};
+use image::{png::PNGEncoder, ColorType, Rgba, RgbaImage};
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::ops::Range;
+use std::path::Path;
+use std::time::Duration;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnimationOptions {
+    /// Frames to use out of the ones given, all of them by default.
+    pub range: Option<Range<usize>>,
+    /// How long each frame stays up.
+    pub delay: Duration,
+    /// Times to play, `None` loops forever.
+    pub loops: Option<u16>,
+    /// Size factor applied to every frame, 0.5 halves width and height.
+    pub scale: f32,
+    /// Palette size for GIF, at most 256.
+    pub colors: usize,
+    /// Floyd-Steinberg dithering against the GIF palette.
+    pub dither: bool,
+}
+
+impl Default for AnimationOptions {
+    fn default() -> Self {
+        AnimationOptions {
+            range: None,
+            delay: Duration::from_millis(40),
+            loops: None,
+            scale: 1.,
+            colors: 256,
+            dither: true,
+        }
+    }
+}
+
+impl AnimationOptions {
+    /// The selected frames, scaled.
+    fn frames(&self, frames: &[RgbaImage]) -> Vec<RgbaImage> {
+        let range = self.range.clone().unwrap_or(0..frames.len());
+        let end = range.end.min(frames.len());
+        let range = range.start.min(end)..end;
+        frames[range]
+            .iter()
+            .map(|frame| {
+                if self.scale == 1. {
+                    return frame.clone();
+                }
+                let w = ((frame.width() as f32 * self.scale).round() as u32).max(1);
+                let h = ((frame.height() as f32 * self.scale).round() as u32).max(1);
+                imageops::resize(frame, w, h, FilterType::Triangle)
+            })
+            .collect()
+    }
+}
+
+/// Palette shared by all frames so colors don't flicker between them.
+struct Palette(color_quant::NeuQuant);
+
+impl ColorMap for Palette {
+    type Color = Rgba<u8>;
+
+    fn index_of(&self, color: &Rgba<u8>) -> usize {
+        self.0.index_of(&color.0)
+    }
+
+    fn map_color(&self, color: &mut Rgba<u8>) {
+        self.0.map_pixel(&mut color.0)
+    }
+}
+
+impl Palette {
+    /// Train on pixels sampled across every frame.
+    fn new(frames: &[RgbaImage], colors: usize) -> Self {
+        const SAMPLES: usize = 1 << 18;
+        let total: usize = frames
+            .iter()
+            .map(|f| (f.width() * f.height()) as usize)
+            .sum();
+        let step = (total / SAMPLES).max(1);
+        let pixels: Vec<u8> = frames
+            .iter()
+            .flat_map(|f| f.pixels())
+            .step_by(step)
+            .flat_map(|p| vec![p[0], p[1], p[2], 255])
+            .collect();
+        Palette(color_quant::NeuQuant::new(
+            10,
+            colors.clamp(2, 256),
+            &pixels,
+        ))
+    }
+}
+
+/// Size shared by all frames, checked before anything is written.
+fn dimensions(frames: &[RgbaImage]) -> io::Result<(u32, u32)> {
+    let size = match frames.first() {
+        Some(frame) => frame.dimensions(),
+        None => return Err(io::Error::new(io::ErrorKind::InvalidInput, "no frames")),
+    };
+    if frames.iter().any(|frame| frame.dimensions() != size) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "frames differ in size",
+        ));
+    }
+    Ok(size)
+}
+
+pub fn save_gif<P: AsRef<Path>>(
+    file: P,
+    frames: &[RgbaImage],
+    options: &AnimationOptions,
+) -> io::Result<()> {
+    let frames = options.frames(frames);
+    let (width, height) = dimensions(&frames)?;
+    if width > u16::MAX as u32 || height > u16::MAX as u32 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("{}x{} is too large for a GIF", width, height),
+        ));
+    }
+    let palette = Palette::new(&frames, options.colors);
+
+    let file = BufWriter::new(File::create(file)?);
+    let mut encoder = gif::Encoder::new(
+        file,
+        width as u16,
+        height as u16,
+        &palette.0.color_map_rgb(),
+    )?;
+    encoder.set(match options.loops {
+        Some(n) => gif::Repeat::Finite(n),
+        None => gif::Repeat::Infinite,
+    })?;
+    let delay = (options.delay.as_millis() / 10) as u16;
+    for mut frame in frames {
+        if options.dither {
+            imageops::dither(&mut frame, &palette);
+        }
+        let indices = imageops::index_colors(&frame, &palette).into_raw();
+        let mut frame =
+            gif::Frame::from_indexed_pixels(width as u16, height as u16, &indices, None);
+        frame.delay = delay;
+        encoder.write_frame(&frame)?;
+    }
+    Ok(())
+}
+
+/// Lossless animated PNG, frames are encoded as regular PNGs and their
+/// image data is moved into APNG frame chunks.
+pub fn save_apng<P: AsRef<Path>>(
+    file: P,
+    frames: &[RgbaImage],
+    options: &AnimationOptions,
+) -> io::Result<()> {
+    let frames = options.frames(frames);
+    let (width, height) = dimensions(&frames)?;
+    let delay = options.delay.as_millis().min(u16::MAX as u128) as u16;
+
+    let mut out = Vec::new();
+    out.extend_from_slice(b"\x89PNG\r\n\x1a\n");
+    let mut sequence = 0u32;
+    for (i, frame) in frames.iter().enumerate() {
+        let mut png = Vec::new();
+        PNGEncoder::new(&mut png).encode(frame, width, height, ColorType::RGBA(8))?;
+        let chunks = chunks(&png)?;
+
+        if i == 0 {
+            let ihdr = chunks.iter().find(|(kind, _)| kind == b"IHDR");
+            let ihdr = ihdr.ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "no IHDR"))?;
+            write_chunk(&mut out, b"IHDR", ihdr.1);
+            let mut actl = Vec::new();
+            actl.extend_from_slice(&(frames.len() as u32).to_be_bytes());
+            actl.extend_from_slice(&(options.loops.unwrap_or(0) as u32).to_be_bytes());
+            write_chunk(&mut out, b"acTL", &actl);
+        }
+
+        let mut fctl = Vec::new();
+        fctl.extend_from_slice(&sequence.to_be_bytes());
+        fctl.extend_from_slice(&width.to_be_bytes());
+        fctl.extend_from_slice(&height.to_be_bytes());
+        fctl.extend_from_slice(&[0; 8]);
+        fctl.extend_from_slice(&delay.to_be_bytes());
+        fctl.extend_from_slice(&1000u16.to_be_bytes());
+        fctl.extend_from_slice(&[0, 0]);
+        write_chunk(&mut out, b"fcTL", &fctl);
+        sequence += 1;
+
+        for (_, data) in chunks.iter().filter(|(kind, _)| kind == b"IDAT") {
+            if i == 0 {
+                write_chunk(&mut out, b"IDAT", data);
+            } else {
+                let mut fdat = sequence.to_be_bytes().to_vec();
+                fdat.extend_from_slice(data);
+                write_chunk(&mut out, b"fdAT", &fdat);
+                sequence += 1;
+            }
+        }
+    }
+    write_chunk(&mut out, b"IEND", &[]);
+
+    fs::write(file, out)
+}
+
+/// Split an encoded PNG into its chunks.
+fn chunks(png: &[u8]) -> io::Result<Vec<([u8; 4], &[u8])>> {
+    let invalid = || io::Error::new(io::ErrorKind::InvalidData, "truncated PNG");
+    let mut chunks = Vec::new();
+    let mut rest = png.get(8..).ok_or_else(invalid)?;
+    while rest.len() >= 12 {
+        let len = u32::from_be_bytes([rest[0], rest[1], rest[2], rest[3]]) as usize;
+        let kind = [rest[4], rest[5], rest[6], rest[7]];
+        let data = rest.get(8..8 + len).ok_or_else(invalid)?;
+        chunks.push((kind, data));
+        rest = rest.get(12 + len..).ok_or_else(invalid)?;
+    }
+    Ok(chunks)
+}
+
+fn write_chunk<W: Write>(out: &mut W, kind: &[u8; 4], data: &[u8]) {
+    let mut crc = Crc::new();
+    crc.update(kind);
+    crc.update(data);
+    out.write_all(&(data.len() as u32).to_be_bytes()).unwrap();
+    out.write_all(kind).unwrap();
+    out.write_all(data).unwrap();
+    out.write_all(&crc.finish().to_be_bytes()).unwrap();
+}
+
+/// CRC-32 as used by PNG chunks.
+struct Crc(u32);
+
+impl Crc {
+    fn new() -> Self {
+        Crc(0xffff_ffff)
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        for &byte in data {
+            self.0 ^= byte as u32;
+            for _ in 0..8 {
+                let mask = (!(self.0 & 1)).wrapping_add(1);
+                self.0 = (self.0 >> 1) ^ (0xedb8_8320 & mask);
+            }
+        }
+    }
+
+    fn finish(&self) -> u32 {
+        !self.0
+    }
+}
+
+/// Mean per channel difference of two frames, 0 for identical ones and 1
+/// for frames that share nothing, including different sizes.
+pub fn difference(a: &RgbaImage, b: &RgbaImage) -> f32 {
+    if a.dimensions() != b.dimensions() || a.width() == 0 || a.height() == 0 {
+        return 1.;
+    }
+    let sum: u64 = a
+        .pixels()
+        .zip(b.pixels())
+        .map(|(p, q)| {
+            (0..3)
+                .map(|c| (p[c] as i32 - q[c] as i32).unsigned_abs() as u64)
+                .sum::<u64>()
+        })
+        .sum();
+    sum as f32 / (a.width() * a.height() * 3 * 255) as f32
+}
+
+/// Watches frames for the moment the animation comes back to its first
+/// frame.
+///
+/// A loop is reported once the animation has moved away from the first
+/// frame and then came closest to it again, within `tolerance`.
+pub struct LoopDetector {
+    first: Option<RgbaImage>,
+    tolerance: f32,
+    frames: usize,
+    departed: bool,
+    candidate: Option<f32>,
+}
+
+impl LoopDetector {
+    pub fn new(tolerance: f32) -> Self {
+        LoopDetector {
+            first: None,
+            tolerance,
+            frames: 0,
+            departed: false,
+            candidate: None,
+        }
+    }
+
+    /// Feed the next frame, returns the loop length once the previous frame
+    /// turned out to be the closest match.
+    pub fn push(&mut self, frame: &RgbaImage) -> Option<usize> {
+        let index = self.frames;
+        self.frames += 1;
+        let first = match &self.first {
+            Some(first) => first,
+            None => {
+                self.first = Some(frame.clone());
+                return None;
+            }
+        };
+
+        let d = difference(first, frame);
+        let found = match self.candidate {
+            Some(best) if d > best => Some(index - 1),
+            _ => None,
+        };
+        self.departed |= d > self.tolerance;
+        self.candidate = if self.departed && d <= self.tolerance {
+            Some(d)
+        } else {
+            None
+        };
+        found
+    }
+
+    /// Loop length if the last frame pushed closes the loop.
+    pub fn finish(&self) -> Option<usize> {
+        self.candidate.map(|_| self.frames - 1)
+    }
+}
+
+/// Length of the first loop in `frames`, the frames before it make one
+/// seamless cycle.
+pub fn find_loop(frames: &[RgbaImage], tolerance: f32) -> Option<usize> {
+    let mut detector = LoopDetector::new(tolerance);
+    frames
+        .iter()
+        .find_map(|frame| detector.push(frame))
+        .or_else(|| detector.finish())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn frames(count: usize, period: usize) -> Vec<RgbaImage> {
+        (0..count)
+            .map(|i| {
+                let v = ((i % period) * 255 / period) as u8;
+                RgbaImage::from_pixel(8, 8, Rgba([v, 255 - v, 0, 255]))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn finds_loop_length() {
+        assert_eq!(find_loop(&frames(20, 6), 0.01), Some(6));
+        assert_eq!(find_loop(&frames(5, 6), 0.01), None);
+    }
+
+    #[test]
+    fn apng_is_valid_png() {
+        let file = std::env::temp_dir().join(format!("capture-{}.png", std::process::id()));
+        save_apng(&file, &frames(3, 3), &AnimationOptions::default()).unwrap();
+        let png = fs::read(&file).unwrap();
+        let kinds: Vec<[u8; 4]> = chunks(&png).unwrap().iter().map(|c| c.0).collect();
+        assert_eq!(&kinds[..3], &[*b"IHDR", *b"acTL", *b"fcTL"]);
+        assert_eq!(kinds.iter().filter(|k| *k == b"fdAT").count(), 2);
+        // Decoders that don't know APNG still see the first frame.
+        let first = image::open(&file).unwrap().to_rgba();
+        assert_eq!(first.into_raw(), frames(1, 3)[0].clone().into_raw());
+        fs::remove_file(&file).unwrap();
+    }
+
+    #[test]
+    fn gif_round_trips_frames() {
+        let file = std::env::temp_dir().join(format!("capture-{}.gif", std::process::id()));
+        let options = AnimationOptions {
+            range: Some(1..3),
+            scale: 0.5,
+            ..AnimationOptions::default()
+        };
+        save_gif(&file, &frames(4, 4), &options).unwrap();
+        let decoder = image::gif::Decoder::new(File::open(&file).unwrap()).unwrap();
+        let decoded = image::AnimationDecoder::into_frames(decoder)
+            .collect_frames()
+            .unwrap();
+        assert_eq!(decoded.len(), 2);
+        assert_eq!(decoded[0].buffer().dimensions(), (4, 4));
+        fs::remove_file(&file).unwrap();
+    }
+
+    #[test]
+    fn mismatched_frames_are_rejected() {
+        let file = std::env::temp_dir().join(format!("capture-{}-odd", std::process::id()));
+        let mut frames = frames(3, 3);
+        frames[1] = RgbaImage::new(4, 8);
+        let options = AnimationOptions::default();
+        for result in [
+            save_gif(&file, &frames, &options),
+            save_apng(&file, &frames, &options),
+        ]
+        .iter()
+        {
+            let err = result.as_ref().unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        }
+        assert!(!file.exists());
+    }
+
+    #[test]
+    fn ranges_and_sizes_are_checked() {
+        let options = AnimationOptions {
+            range: Some(5..3),
+            ..AnimationOptions::default()
+        };
+        assert!(options.frames(&frames(8, 4)).is_empty());
+        let options = AnimationOptions {
+            range: Some(2..20),
+            ..AnimationOptions::default()
+        };
+        assert_eq!(options.frames(&frames(8, 4)).len(), 6);
+
+        let file = std::env::temp_dir().join(format!("capture-{}-wide.gif", std::process::id()));
+        let wide = vec![RgbaImage::new(u16::MAX as u32 + 1, 1)];
+        let err = save_gif(&file, &wide, &AnimationOptions::default()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        assert!(!file.exists());
+    }
+
+    #[test]
+    fn crc_matches_reference() {
+        let mut crc = Crc::new();
+        crc.update(b"IEND");
+        assert_eq!(crc.finish(), 0xae42_6082);
+    }
+}
@@ -0,0 +1,195 @@
+use crate::FrameWriter;
+use image::RgbaImage;
+
+/// Time source for animated sketches.
+///
+/// In real time it passes the wall clock through. Offline it advances by
+/// exactly `1 / fps` per frame however long the frame took to draw, so
+/// exported frames don't depend on the machine.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Clock {
+    fps: f32,
+    frame: u64,
+    limit: Option<u64>,
+    offline: bool,
+    headless: bool,
+}
+
+impl Clock {
+    pub fn realtime() -> Self {
+        Clock {
+            fps: 60.,
+            frame: 0,
+            limit: None,
+            offline: false,
+            headless: false,
+        }
+    }
+
+    pub fn offline(fps: f32) -> Self {
+        Clock {
+            fps,
+            offline: true,
+            ..Clock::realtime()
+        }
+    }
+
+    /// Stop after `frames` frames.
+    pub fn frames(mut self, frames: u64) -> Self {
+        self.limit = Some(frames);
+        self
+    }
+
+    /// Stop after `seconds` of animation time.
+    pub fn seconds(self, seconds: f32) -> Self {
+        let frames = (seconds * self.fps).ceil() as u64;
+        self.frames(frames)
+    }
+
+    /// Render without a window, implies offline.
+    pub fn headless(mut self) -> Self {
+        self.offline = true;
+        self.headless = true;
+        self
+    }
+
+    /// Read `--fps N`, `--frames N`, `--seconds S` and `--headless` from
+    /// the command line, other arguments are left for the sketch. Without
+    /// any of them the clock runs in real time, a bad value exits.
+    pub fn from_args() -> Self {
+        Self::parse(std::env::args().skip(1)).unwrap_or_else(|err| {
+            eprintln!("{}", err);
+            std::process::exit(2);
+        })
+    }
+
+    pub fn parse<I: IntoIterator<Item = String>>(args: I) -> Result<Self, String> {
+        let (mut fps, mut frames, mut seconds, mut headless) = (None, None, None, false);
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let mut value = || {
+                let value = args.next().unwrap_or_default();
+                value
+                    .parse::<f32>()
+                    .map_err(|_| format!("{} needs a number, got `{}`", arg, value))
+            };
+            match arg.as_str() {
+                "--fps" => fps = Some(value()?),
+                "--frames" => frames = Some(value()?),
+                "--seconds" => seconds = Some(value()?),
+                "--headless" => headless = true,
+                _ => {}
+            }
+        }
+        if let Some(fps) = fps {
+            if !(fps > 0.) {
+                return Err(format!("--fps has to be above 0, got {}", fps));
+            }
+        }
+        if fps.is_none() && frames.is_none() && seconds.is_none() && !headless {
+            return Ok(Clock::realtime());
+        }
+
+        let mut clock = Clock::offline(fps.unwrap_or(60.));
+        if let Some(frames) = frames {
+            clock = clock.frames(frames as u64);
+        } else if let Some(seconds) = seconds {
+            clock = clock.seconds(seconds);
+        }
+        if headless {
+            clock = clock.headless();
+        }
+        Ok(clock)
+    }
+
+    pub fn is_offline(&self) -> bool {
+        self.offline
+    }
+
+    pub fn is_headless(&self) -> bool {
+        self.headless
+    }
+
+    pub fn fps(&self) -> f32 {
+        self.fps
+    }
+
+    /// Frames handed out by `tick` so far.
+    pub fn frame(&self) -> u64 {
+        self.frame
+    }
+
+    /// Time of the current frame, `wall` is what the app says it is.
+    pub fn time(&self, wall: f32) -> f32 {
+        if self.offline {
+            self.frame as f32 / self.fps
+        } else {
+            wall
+        }
+    }
+
+    /// Time of the frame about to be drawn, moves on to the next one.
+    pub fn tick(&mut self, wall: f32) -> f32 {
+        let time = self.time(wall);
+        self.frame += 1;
+        time
+    }
+
+    /// Whether all requested frames have been handed out.
+    pub fn finished(&self) -> bool {
+        self.limit.map(|limit| self.frame >= limit).unwrap_or(false)
+    }
+}
+
+/// Draw every frame of an offline clock with `frame` and queue it on
+/// `writer`. The clock needs a frame count, there is no window to close.
+pub fn render<F>(mut clock: Clock, writer: &mut FrameWriter, mut frame: F)
+where
+    F: FnMut(f32) -> RgbaImage,
+{
+    assert!(clock.offline, "rendering needs an offline clock");
+    assert!(clock.limit.is_some(), "rendering needs a frame count");
+    while !clock.finished() {
+        let time = clock.tick(0.);
+        writer.push(frame(time));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn parse(args: &str) -> Clock {
+        Clock::parse(args.split_whitespace().map(String::from)).unwrap()
+    }
+
+    #[test]
+    fn parses_command_line() {
+        assert_eq!(parse("--seed 3"), Clock::realtime());
+        let clock = parse("--fps 30 --seconds 2 --headless");
+        assert!(clock.is_offline() && clock.is_headless());
+        assert_eq!(clock.limit, Some(60));
+        assert_eq!(parse("--frames 10").fps(), 60.);
+        for bad in &[
+            "--fps 0",
+            "--fps -5",
+            "--fps NaN",
+            "--frames",
+            "--seconds x",
+        ] {
+            assert!(Clock::parse(bad.split_whitespace().map(String::from)).is_err());
+        }
+    }
+
+    #[test]
+    fn fixed_steps_ignore_wall_clock() {
+        let mut clock = Clock::offline(25.).frames(3);
+        let times: Vec<f32> = (0..3).map(|i| clock.tick(i as f32 * 7.)).collect();
+        assert_eq!(times, vec![0., 0.04, 0.08]);
+        assert!(clock.finished());
+
+        let mut clock = Clock::realtime();
+        assert_eq!(clock.tick(1.5), 1.5);
+        assert!(!clock.finished());
+    }
+}
@@ -0,0 +1,145 @@
+use image::RgbaImage;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, SyncSender};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+mod animation;
+mod clock;
+
+pub use animation::{difference, find_loop, save_apng, save_gif, AnimationOptions, LoopDetector};
+pub use clock::{render, Clock};
+
+type Job = Box<dyn FnOnce() -> RgbaImage + Send>;
+
+/// Writes numbered PNG frames from a background thread.
+///
+/// Frames go through a bounded queue, so a slow disk makes `push` block
+/// instead of piling up frames in memory. The numbering matches what
+/// `ffmpeg -i frame_%05d.png` expects.
+pub struct FrameWriter {
+    dir: PathBuf,
+    prefix: String,
+    next: usize,
+    sender: Option<SyncSender<(PathBuf, Job)>>,
+    done: Receiver<()>,
+    worker: Option<JoinHandle<()>>,
+}
+
+impl FrameWriter {
+    /// Start a writer saving into `dir`, which is created if needed, with
+    /// at most `capacity` frames waiting to be encoded.
+    pub fn new<P: AsRef<Path>>(dir: P, capacity: usize) -> io::Result<Self> {
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(&dir)?;
+
+        let (sender, receiver) = mpsc::sync_channel::<(PathBuf, Job)>(capacity);
+        let (done_sender, done) = mpsc::channel();
+        let worker = thread::Builder::new()
+            .name("frame writer".to_string())
+            .spawn(move || {
+                for (path, job) in receiver {
+                    if let Err(err) = job().save(&path) {
+                        eprintln!("failed to write {}: {}", path.display(), err);
+                    }
+                }
+                done_sender.send(()).ok();
+            })?;
+
+        Ok(FrameWriter {
+            dir,
+            prefix: "frame".to_string(),
+            next: 0,
+            sender: Some(sender),
+            done,
+            worker: Some(worker),
+        })
+    }
+
+    /// File name prefix, `frame` by default.
+    pub fn prefix(mut self, prefix: &str) -> Self {
+        self.prefix = prefix.to_string();
+        self
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Number of frames queued so far.
+    pub fn frames(&self) -> usize {
+        self.next
+    }
+
+    pub fn push(&mut self, image: RgbaImage) -> PathBuf {
+        self.push_with(move || image)
+    }
+
+    /// Queue a frame whose pixels are produced on the writer thread, handy
+    /// when they still need converting.
+    pub fn push_with<F>(&mut self, frame: F) -> PathBuf
+    where
+        F: FnOnce() -> RgbaImage + Send + 'static,
+    {
+        let path = self
+            .dir
+            .join(format!("{}_{:05}.png", self.prefix, self.next));
+        self.next += 1;
+        if let Some(sender) = &self.sender {
+            sender
+                .send((path.clone(), Box::new(frame)))
+                .expect("frame writer thread is gone");
+        }
+        path
+    }
+
+    /// Stop accepting frames and wait up to `timeout` for the queue to
+    /// drain. Returns false if frames were still being written.
+    pub fn flush(mut self, timeout: Duration) -> bool {
+        self.sender.take();
+        match self.done.recv_timeout(timeout) {
+            Ok(()) => {
+                if let Some(worker) = self.worker.take() {
+                    worker.join().ok();
+                }
+                true
+            }
+            Err(_) => {
+                // Let the thread finish on its own instead of blocking in drop.
+                self.worker.take();
+                false
+            }
+        }
+    }
+}
+
+impl Drop for FrameWriter {
+    fn drop(&mut self) {
+        self.sender.take();
+        if let Some(worker) = self.worker.take() {
+            worker.join().ok();
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn writes_numbered_frames() {
+        let dir = std::env::temp_dir().join(format!("capture-test-{}", std::process::id()));
+        let mut writer = FrameWriter::new(&dir, 1).unwrap().prefix("shot");
+        for i in 0..3 {
+            writer.push_with(move || RgbaImage::from_pixel(4, 4, image::Rgba([i, 0, 0, 255])));
+        }
+        assert_eq!(writer.frames(), 3);
+        assert!(writer.flush(Duration::from_secs(10)));
+
+        let last = image::open(dir.join("shot_00002.png")).unwrap().to_rgba();
+        assert_eq!(*last.get_pixel(0, 0), image::Rgba([2, 0, 0, 255]));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
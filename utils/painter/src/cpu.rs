@@ -0,0 +1,205 @@
+use crate::Painter;
+use canvas::{raster, Canvas};
+use image::RgbaImage;
+use nannou::prelude::*;
+use rusttype::{point, Font, Scale};
+use std::cell::RefCell;
+use std::path::Path;
+
+/// Software rasteriser painting into a `canvas::Canvas`.
+///
+/// Needs neither a window nor a GPU, so it runs in CI and on headless
+/// boxes. Shapes are anti-aliased and composited by the canvas, with its
+/// blend mode.
+pub struct Raster {
+    canvas: RefCell<Canvas>,
+    font: Font<'static>,
+}
+
+impl Raster {
+    pub fn new(width: u32, height: u32) -> Self {
+        Self::from_canvas(Canvas::new(width, height))
+    }
+
+    pub fn from_canvas(canvas: Canvas) -> Self {
+        let font = Font::from_bytes(notosans::REGULAR_TTF).expect("bundled font is valid");
+        Raster {
+            canvas: RefCell::new(canvas),
+            font,
+        }
+    }
+
+    pub fn width(&self) -> u32 {
+        self.canvas.borrow().width()
+    }
+
+    pub fn height(&self) -> u32 {
+        self.canvas.borrow().height()
+    }
+
+    /// Copy of the current image.
+    pub fn image(&self) -> RgbaImage {
+        self.canvas.borrow().to_image()
+    }
+
+    pub fn into_image(self) -> RgbaImage {
+        self.canvas.into_inner().to_image()
+    }
+
+    pub fn into_canvas(self) -> Canvas {
+        self.canvas.into_inner()
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> std::io::Result<()> {
+        self.canvas.borrow().save(path)
+    }
+
+    fn to_pixel(&self, p: Point2) -> Point2 {
+        self.canvas.borrow().to_pixel(p)
+    }
+
+    fn fill(&self, points: &[Point2], color: Srgba) {
+        self.canvas.borrow_mut().fill_pixels(points, color);
+    }
+}
+
+impl Painter for Raster {
+    fn background(&self, color: Srgba) {
+        let mut canvas = self.canvas.borrow_mut();
+        canvas.set_background(color);
+        canvas.clear();
+    }
+
+    fn line(&self, start: Point2, end: Point2, weight: f32, color: Srgba) {
+        let outline = raster::stroke_segment(self.to_pixel(start), self.to_pixel(end), weight);
+        self.fill(&outline, color);
+    }
+
+    fn polyline(&self, points: &[Point2], weight: f32, color: Srgba) {
+        let points: Vec<Point2> = points.iter().map(|&p| self.to_pixel(p)).collect();
+        self.fill(&raster::stroke_polyline(&points, weight), color);
+    }
+
+    fn polygon(&self, points: &[Point2], color: Srgba) {
+        let points: Vec<Point2> = points.iter().map(|&p| self.to_pixel(p)).collect();
+        self.fill(&points, color);
+    }
+
+    fn ellipse(&self, center: Point2, w: f32, h: f32, color: Srgba) {
+        self.fill(
+            &raster::ellipse(self.to_pixel(center), w / 2., h / 2.),
+            color,
+        );
+    }
+
+    fn mesh(&self, vertices: &[(Point2, Srgba)]) {
+        for tri in vertices.chunks(3).filter(|t| t.len() == 3) {
+            let p = [
+                self.to_pixel(tri[0].0),
+                self.to_pixel(tri[1].0),
+                self.to_pixel(tri[2].0),
+            ];
+            let area = cross(p[1] - p[0], p[2] - p[0]);
+            if area == 0. {
+                continue;
+            }
+            let mut canvas = self.canvas.borrow_mut();
+            let (w, h) = (canvas.width(), canvas.height());
+            raster::fill_polygon(&p, w, h, |x, y, c| {
+                let q = pt2(x as f32 + 0.5, y as f32 + 0.5);
+                let b0 = (cross(p[1] - q, p[2] - q) / area).clamp(0., 1.);
+                let b1 = (cross(p[2] - q, p[0] - q) / area).clamp(0., 1.);
+                let b2 = (1. - b0 - b1).clamp(0., 1.);
+                let mix = |f: fn(&Srgba) -> f32| {
+                    b0 * f(&tri[0].1) + b1 * f(&tri[1].1) + b2 * f(&tri[2].1)
+                };
+                let color = Srgba::new(
+                    mix(|c| c.red),
+                    mix(|c| c.green),
+                    mix(|c| c.blue),
+                    mix(|c| c.alpha),
+                );
+                canvas.blend_pixel(x, y, color, c);
+            });
+        }
+    }
+
+    fn text(&self, text: &str, rect: Rect, size: u32, color: Srgba) {
+        let scale = Scale::uniform(size as f32);
+        let v_metrics = self.font.v_metrics(scale);
+        let width = self
+            .font
+            .layout(text, scale, point(0., 0.))
+            .last()
+            .map(|g| g.position().x + g.unpositioned().h_metrics().advance_width)
+            .unwrap_or(0.);
+
+        let center = self.to_pixel(pt2(rect.x(), rect.y()));
+        let baseline = center.y + (v_metrics.ascent + v_metrics.descent) / 2.;
+        let start = point(center.x - width / 2., baseline);
+
+        let mut canvas = self.canvas.borrow_mut();
+        for glyph in self.font.layout(text, scale, start) {
+            if let Some(bb) = glyph.pixel_bounding_box() {
+                glyph.draw(|gx, gy, v| {
+                    let x = bb.min.x + gx as i32;
+                    let y = bb.min.y + gy as i32;
+                    if x >= 0 && y >= 0 {
+                        canvas.blend_pixel(x as u32, y as u32, color, v);
+                    }
+                });
+            }
+        }
+    }
+}
+
+fn cross(a: Vector2, b: Vector2) -> f32 {
+    a.x * b.y - a.y * b.x
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use image::Rgba;
+
+    fn solid(r: f32, g: f32, b: f32) -> Srgba {
+        Srgba::new(r, g, b, 1.)
+    }
+
+    #[test]
+    fn shapes_land_in_sketch_coordinates() {
+        let raster = Raster::new(20, 20);
+        raster.background(solid(0., 0., 0.));
+        raster.rect(pt2(5., 5.), 4., 4., solid(1., 0., 0.));
+        raster.ellipse(pt2(-5., -5.), 6., 6., solid(0., 1., 0.));
+        let image = raster.into_image();
+        // y points up in the sketch and down in the image
+        assert_eq!(*image.get_pixel(15, 5), Rgba([255, 0, 0, 255]));
+        assert_eq!(*image.get_pixel(5, 15), Rgba([0, 255, 0, 255]));
+        assert_eq!(*image.get_pixel(10, 10), Rgba([0, 0, 0, 255]));
+    }
+
+    #[test]
+    fn mesh_interpolates_vertex_colors() {
+        let raster = Raster::new(20, 20);
+        raster.mesh(&[
+            (pt2(-10., -10.), solid(1., 0., 0.)),
+            (pt2(10., -10.), solid(0., 0., 1.)),
+            (pt2(-10., 10.), solid(1., 0., 0.)),
+        ]);
+        let image = raster.into_image();
+        let Rgba([r0, _, b0, _]) = *image.get_pixel(1, 18);
+        let Rgba([r1, _, b1, _]) = *image.get_pixel(17, 18);
+        assert!(r0 > r1 && b0 < b1);
+    }
+
+    #[test]
+    fn text_and_lines_draw_something() {
+        let raster = Raster::new(64, 32);
+        raster.text("Hi", Rect::from_w_h(64., 32.), 20, solid(1., 1., 1.));
+        raster.line(pt2(-32., -15.), pt2(32., -15.), 2., solid(1., 1., 1.));
+        let image = raster.into_image();
+        assert!(image.pixels().filter(|p| p[3] > 0).count() > 64 * 2);
+        assert!(image.get_pixel(32, 16)[3] > 0 || image.get_pixel(28, 16)[3] > 0);
+    }
+}
@@ -0,0 +1,108 @@
+use nannou::prelude::*;
+
+mod cpu;
+mod viewport;
+
+pub use cpu::Raster;
+pub use viewport::Viewport;
+
+/// The drawing calls the sketches need, independent of where they end up.
+///
+/// Implemented for nannou's `Draw`, which needs a window and a GPU, and for
+/// `Raster`, which rasterises on the CPU into an `image::RgbaImage`. All
+/// coordinates are sketch coordinates: origin in the middle, y pointing up.
+pub trait Painter {
+    fn background(&self, color: Srgba);
+
+    fn line(&self, start: Point2, end: Point2, weight: f32, color: Srgba);
+
+    fn polyline(&self, points: &[Point2], weight: f32, color: Srgba);
+
+    fn polygon(&self, points: &[Point2], color: Srgba);
+
+    fn ellipse(&self, center: Point2, w: f32, h: f32, color: Srgba);
+
+    fn rect(&self, center: Point2, w: f32, h: f32, color: Srgba) {
+        let (hw, hh) = (w / 2., h / 2.);
+        let corners = [
+            center + vec2(-hw, -hh),
+            center + vec2(hw, -hh),
+            center + vec2(hw, hh),
+            center + vec2(-hw, hh),
+        ];
+        self.polygon(&corners, color);
+    }
+
+    /// Triangle list, every three vertices make one triangle and the
+    /// colors are interpolated across it.
+    fn mesh(&self, vertices: &[(Point2, Srgba)]);
+
+    /// Single line of text centered in `rect`.
+    fn text(&self, text: &str, rect: Rect, size: u32, color: Srgba);
+}
+
+impl<'a> Painter for nannou::app::Draw<'a> {
+    fn background(&self, color: Srgba) {
+        (**self).background().color(color);
+    }
+
+    fn line(&self, start: Point2, end: Point2, weight: f32, color: Srgba) {
+        (**self)
+            .line()
+            .start(start)
+            .end(end)
+            .weight(weight)
+            .color(color);
+    }
+
+    fn polyline(&self, points: &[Point2], weight: f32, color: Srgba) {
+        (**self)
+            .polyline()
+            .weight(weight)
+            .color(color)
+            .points(points.iter().cloned());
+    }
+
+    fn polygon(&self, points: &[Point2], color: Srgba) {
+        (**self)
+            .polygon()
+            .color(color)
+            .points(points.iter().cloned());
+    }
+
+    fn ellipse(&self, center: Point2, w: f32, h: f32, color: Srgba) {
+        (**self)
+            .ellipse()
+            .x_y(center.x, center.y)
+            .w_h(w, h)
+            .color(color);
+    }
+
+    fn rect(&self, center: Point2, w: f32, h: f32, color: Srgba) {
+        (**self)
+            .rect()
+            .x_y(center.x, center.y)
+            .w_h(w, h)
+            .color(color);
+    }
+
+    fn mesh(&self, vertices: &[(Point2, Srgba)]) {
+        let tris = vertices.chunks(3).filter(|t| t.len() == 3).map(|t| {
+            geom::Tri([
+                geom::vertex::Srgba(t[0].0, t[0].1),
+                geom::vertex::Srgba(t[1].0, t[1].1),
+                geom::vertex::Srgba(t[2].0, t[2].1),
+            ])
+        });
+        (**self).mesh().tris(tris);
+    }
+
+    fn text(&self, text: &str, rect: Rect, size: u32, color: Srgba) {
+        let text = nannou::text::text(text).font_size(size).build(rect);
+        (**self)
+            .path()
+            .fill()
+            .color(color)
+            .events(text.path_events());
+    }
+}
@@ -0,0 +1,106 @@
+use crate::Painter;
+use nannou::prelude::*;
+
+/// Draws a whole sketch into one rectangle of another painter.
+///
+/// `from` is the area the sketch thinks it draws into, it is scaled
+/// uniformly and centred in `to`. Line weights and text sizes scale along.
+/// Nothing is clipped, shapes outside `from` spill out of `to` as well.
+pub struct Viewport<'a, P: ?Sized> {
+    painter: &'a P,
+    from: Rect,
+    to: Rect,
+    scale: f32,
+}
+
+impl<'a, P: Painter + ?Sized> Viewport<'a, P> {
+    pub fn new(painter: &'a P, from: Rect, to: Rect) -> Self {
+        let scale = (to.w() / from.w()).min(to.h() / from.h());
+        Viewport {
+            painter,
+            from,
+            to,
+            scale,
+        }
+    }
+
+    pub fn scale(&self) -> f32 {
+        self.scale
+    }
+
+    fn map(&self, p: Point2) -> Point2 {
+        self.to.xy() + (p - self.from.xy()) * self.scale
+    }
+
+    fn map_all(&self, points: &[Point2]) -> Vec<Point2> {
+        points.iter().map(|&p| self.map(p)).collect()
+    }
+}
+
+impl<'a, P: Painter + ?Sized> Painter for Viewport<'a, P> {
+    /// Fills the sketch area only, not the whole target.
+    fn background(&self, color: Srgba) {
+        let (w, h) = (self.from.w() * self.scale, self.from.h() * self.scale);
+        self.painter.rect(self.to.xy(), w, h, color);
+    }
+
+    fn line(&self, start: Point2, end: Point2, weight: f32, color: Srgba) {
+        let (start, end) = (self.map(start), self.map(end));
+        self.painter.line(start, end, weight * self.scale, color);
+    }
+
+    fn polyline(&self, points: &[Point2], weight: f32, color: Srgba) {
+        let points = self.map_all(points);
+        self.painter.polyline(&points, weight * self.scale, color);
+    }
+
+    fn polygon(&self, points: &[Point2], color: Srgba) {
+        self.painter.polygon(&self.map_all(points), color);
+    }
+
+    fn ellipse(&self, center: Point2, w: f32, h: f32, color: Srgba) {
+        let center = self.map(center);
+        self.painter
+            .ellipse(center, w * self.scale, h * self.scale, color);
+    }
+
+    fn rect(&self, center: Point2, w: f32, h: f32, color: Srgba) {
+        let center = self.map(center);
+        self.painter
+            .rect(center, w * self.scale, h * self.scale, color);
+    }
+
+    fn mesh(&self, vertices: &[(Point2, Srgba)]) {
+        let vertices: Vec<_> = vertices.iter().map(|&(p, c)| (self.map(p), c)).collect();
+        self.painter.mesh(&vertices);
+    }
+
+    fn text(&self, text: &str, rect: Rect, size: u32, color: Srgba) {
+        let center = self.map(rect.xy());
+        let (w, h) = (rect.w() * self.scale, rect.h() * self.scale);
+        let rect = Rect::from_x_y_w_h(center.x, center.y, w, h);
+        let size = ((size as f32 * self.scale).round() as u32).max(1);
+        self.painter.text(text, rect, size, color);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Raster;
+
+    #[test]
+    fn sketch_lands_in_its_cell() {
+        let raster = Raster::new(40, 20);
+        let cell = Rect::from_x_y_w_h(10., 0., 20., 20.);
+        let viewport = Viewport::new(&raster, Rect::from_w_h(100., 100.), cell);
+        assert_eq!(viewport.scale(), 0.2);
+        viewport.background(Srgba::new(1., 0., 0., 1.));
+        viewport.rect(pt2(25., 25.), 50., 50., Srgba::new(0., 1., 0., 1.));
+
+        let image = raster.into_image();
+        assert_eq!(image.get_pixel(5, 10)[3], 0);
+        assert_eq!(image.get_pixel(25, 15).0, [255, 0, 0, 255]);
+        assert_eq!(image.get_pixel(35, 5).0, [0, 255, 0, 255]);
+    }
+}
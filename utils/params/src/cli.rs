@@ -0,0 +1,59 @@
+//! Command line flags shared by the sketches and the launcher.
+use crate::Value;
+use std::path::PathBuf;
+
+/// Window size from `--size WxH`, `default` without it.
+pub fn size(default: (u32, u32)) -> (u32, u32) {
+    flag("--size")
+        .and_then(|s| parse_size(&s))
+        .unwrap_or(default)
+}
+
+/// `--render FILE`: draw a single still into FILE, PNG or SVG by its
+/// extension, and exit without opening a window.
+pub fn render_target() -> Option<PathBuf> {
+    flag("--render").map(PathBuf::from)
+}
+
+/// `--sheet FILE`: draw a contact sheet, see `Sheet::from_args`, into FILE
+/// and exit without opening a window.
+pub fn sheet_target() -> Option<PathBuf> {
+    flag("--sheet").map(PathBuf::from)
+}
+
+fn flag(name: &str) -> Option<String> {
+    let mut args = std::env::args().skip(1);
+    args.position(|arg| arg == name)?;
+    args.next()
+}
+
+pub fn parse_size(size: &str) -> Option<(u32, u32)> {
+    let mut parts = size.splitn(2, &['x', 'X'][..]);
+    let w = parts.next()?.trim().parse().ok()?;
+    let h = parts.next()?.trim().parse().ok()?;
+    Some((w, h))
+}
+
+/// `name=value` as given to `--set`.
+pub fn parse_assignment(assignment: &str) -> Option<(String, Value)> {
+    let mut parts = assignment.splitn(2, '=');
+    let name = parts.next()?.trim();
+    let value = crate::preset::parse_value(parts.next()?.trim())?;
+    Some((name.to_string(), value))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parses_flags() {
+        assert_eq!(parse_size("1920x1080"), Some((1920, 1080)));
+        assert_eq!(parse_size("720"), None);
+        assert_eq!(
+            parse_assignment("ratio=0.5"),
+            Some(("ratio".to_string(), Value::Float(0.5)))
+        );
+        assert_eq!(parse_assignment("closed"), None);
+    }
+}
@@ -0,0 +1,293 @@
+use std::error::Error;
+use std::fmt;
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
+
+pub mod cli;
+mod panel;
+mod preset;
+pub mod sheet;
+mod watch;
+
+pub use panel::Panel;
+pub use preset::ParseError;
+pub use sheet::Sheet;
+pub use watch::Watcher;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Value {
+    Float(f32),
+    Int(i64),
+    Bool(bool),
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Value::Float(v) => write!(f, "{:.3}", v),
+            Value::Int(v) => write!(f, "{}", v),
+            Value::Bool(v) => write!(f, "{}", v),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Kind {
+    Float { min: f32, max: f32 },
+    Int { min: i64, max: i64 },
+    Toggle,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Param {
+    pub name: String,
+    pub kind: Kind,
+    pub default: Value,
+    value: Value,
+}
+
+impl Param {
+    pub fn value(&self) -> Value {
+        self.value
+    }
+
+    /// `value` converted to this parameter's type and range. Numbers convert
+    /// into each other, sliders only hand out floats.
+    fn accept(&self, value: Value) -> Option<Value> {
+        match (self.kind, value) {
+            (Kind::Float { min, max }, Value::Float(v)) => Some(Value::Float(v.clamp(min, max))),
+            (Kind::Float { min, max }, Value::Int(v)) => {
+                Some(Value::Float((v as f32).clamp(min, max)))
+            }
+            (Kind::Int { min, max }, Value::Int(v)) => Some(Value::Int(v.clamp(min, max))),
+            (Kind::Int { min, max }, Value::Float(v)) => {
+                Some(Value::Int((v.round() as i64).clamp(min, max)))
+            }
+            (Kind::Toggle, Value::Bool(v)) => Some(Value::Bool(v)),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParamError {
+    Unknown(String),
+    Type { name: String, value: Value },
+}
+
+impl fmt::Display for ParamError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParamError::Unknown(name) => write!(f, "no parameter `{}`", name),
+            ParamError::Type { name, value } => {
+                write!(f, "parameter `{}` can't be set to {}", name, value)
+            }
+        }
+    }
+}
+
+impl Error for ParamError {}
+
+/// Named, typed and bounded values a sketch reads instead of constants.
+///
+/// Every change is remembered until `changes` is called, which is how the
+/// panel knows what to regenerate. The RNG seed travels along so a preset
+/// reproduces random sketches too, it reports its changes as `seed`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Params {
+    params: Vec<Param>,
+    changed: Vec<usize>,
+    seed: u64,
+    reseeded: bool,
+    source: Option<PathBuf>,
+}
+
+impl Params {
+    /// No parameters yet and a random seed.
+    pub fn new() -> Self {
+        Params {
+            seed: nannou::rand::random::<u32>() as u64,
+            ..Params::default()
+        }
+    }
+
+    pub fn float(self, name: &str, default: f32, range: RangeInclusive<f32>) -> Self {
+        let (min, max) = range.into_inner();
+        self.with(name, Kind::Float { min, max }, Value::Float(default))
+    }
+
+    pub fn int(self, name: &str, default: i64, range: RangeInclusive<i64>) -> Self {
+        let (min, max) = range.into_inner();
+        self.with(name, Kind::Int { min, max }, Value::Int(default))
+    }
+
+    pub fn toggle(self, name: &str, default: bool) -> Self {
+        self.with(name, Kind::Toggle, Value::Bool(default))
+    }
+
+    fn with(mut self, name: &str, kind: Kind, default: Value) -> Self {
+        assert!(self.get(name).is_none(), "parameter `{}` added twice", name);
+        assert!(name != "seed", "`seed` is reserved for the RNG seed");
+        let mut param = Param {
+            name: name.to_string(),
+            kind,
+            default,
+            value: default,
+        };
+        param.default = param.accept(default).unwrap();
+        param.value = param.default;
+        self.params.push(param);
+        self
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Param> {
+        self.params.iter().find(|p| p.name == name)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Param> {
+        self.params.iter()
+    }
+
+    fn value(&self, name: &str) -> Value {
+        match self.get(name) {
+            Some(param) => param.value,
+            None => panic!("no parameter `{}`", name),
+        }
+    }
+
+    pub fn f32(&self, name: &str) -> f32 {
+        match self.value(name) {
+            Value::Float(v) => v,
+            Value::Int(v) => v as f32,
+            Value::Bool(_) => panic!("parameter `{}` is not a number", name),
+        }
+    }
+
+    pub fn i64(&self, name: &str) -> i64 {
+        match self.value(name) {
+            Value::Int(v) => v,
+            Value::Float(v) => v.round() as i64,
+            Value::Bool(_) => panic!("parameter `{}` is not a number", name),
+        }
+    }
+
+    pub fn usize(&self, name: &str) -> usize {
+        self.i64(name).max(0) as usize
+    }
+
+    pub fn bool(&self, name: &str) -> bool {
+        match self.value(name) {
+            Value::Bool(v) => v,
+            _ => panic!("parameter `{}` is not a toggle", name),
+        }
+    }
+
+    /// Set a parameter, clamped to its range. Returns whether the value
+    /// actually changed.
+    pub fn set(&mut self, name: &str, value: Value) -> Result<bool, ParamError> {
+        let index = self
+            .params
+            .iter()
+            .position(|p| p.name == name)
+            .ok_or_else(|| ParamError::Unknown(name.to_string()))?;
+        let param = &mut self.params[index];
+        let value = param.accept(value).ok_or_else(|| ParamError::Type {
+            name: name.to_string(),
+            value,
+        })?;
+        if param.value == value {
+            return Ok(false);
+        }
+        param.value = value;
+        if !self.changed.contains(&index) {
+            self.changed.push(index);
+        }
+        Ok(true)
+    }
+
+    /// Preset file these values were last loaded from.
+    pub fn source(&self) -> Option<&Path> {
+        self.source.as_deref()
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn set_seed(&mut self, seed: u64) {
+        if self.seed != seed {
+            self.seed = seed;
+            self.reseeded = true;
+        }
+    }
+
+    /// Pick a new random seed.
+    pub fn reseed(&mut self) {
+        self.set_seed(nannou::rand::random::<u32>() as u64);
+    }
+
+    /// Put every parameter back to its default.
+    pub fn reset(&mut self) {
+        for i in 0..self.params.len() {
+            let (name, default) = (self.params[i].name.clone(), self.params[i].default);
+            self.set(&name, default).unwrap();
+        }
+    }
+
+    /// Names of the parameters changed since the last call.
+    pub fn changes(&mut self) -> Vec<String> {
+        let params = &self.params;
+        let mut changes: Vec<String> = self
+            .changed
+            .drain(..)
+            .map(|i| params[i].name.clone())
+            .collect();
+        if self.reseeded {
+            self.reseeded = false;
+            changes.push("seed".to_string());
+        }
+        changes
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn params() -> Params {
+        Params::new()
+            .float("ratio", 0.25, 0.0..=0.5)
+            .int("joints", 5, 1..=8)
+            .toggle("closed", false)
+    }
+
+    #[test]
+    fn values_are_clamped_and_converted() {
+        let mut params = params();
+        assert_eq!(params.set("ratio", Value::Float(2.)), Ok(true));
+        assert_eq!(params.f32("ratio"), 0.5);
+        assert_eq!(params.set("joints", Value::Float(2.6)), Ok(true));
+        assert_eq!(params.usize("joints"), 3);
+        assert!(params.set("closed", Value::Int(1)).is_err());
+        assert_eq!(
+            params.set("speed", Value::Int(1)),
+            Err(ParamError::Unknown("speed".to_string()))
+        );
+    }
+
+    #[test]
+    fn changes_are_reported_once() {
+        let mut params = params();
+        params.set("joints", Value::Int(6)).unwrap();
+        params.set("closed", Value::Bool(true)).unwrap();
+        params.set("joints", Value::Int(7)).unwrap();
+        assert_eq!(params.set("ratio", Value::Float(0.25)), Ok(false));
+        assert_eq!(params.changes(), vec!["joints", "closed"]);
+        assert!(params.changes().is_empty());
+
+        params.reset();
+        params.set_seed(params.seed() + 1);
+        assert_eq!(params.changes(), vec!["joints", "closed", "seed"]);
+        assert!(params.iter().eq(self::params().iter()));
+    }
+}
@@ -0,0 +1,187 @@
+use crate::{Kind, Params, Value, Watcher};
+use nannou::prelude::*;
+use nannou::ui::prelude::*;
+
+const WIDTH: f64 = 220.;
+const HEIGHT: f64 = 24.;
+
+type Handler<M> = (Vec<String>, fn(&mut M));
+
+/// Sliders for every parameter in a corner of the window.
+///
+/// Handlers are plain functions of the model so they can run after the
+/// panel lets go of it, see `Panel::update`. A handler only runs for the
+/// names it asked for, so a sketch can rebuild on some parameters and just
+/// read others every frame.
+///
+/// When the parameters came from a preset file the panel watches it and
+/// applies edits while the sketch runs.
+pub struct Panel<M> {
+    pub params: Params,
+    ui: Ui,
+    ids: Vec<widget::Id>,
+    handlers: Vec<Handler<M>>,
+    visible: bool,
+    watcher: Option<Watcher>,
+}
+
+impl<M> Panel<M> {
+    pub fn new(app: &App, window: window::Id, params: Params) -> Self {
+        let mut ui = app.new_ui().window(window).build().unwrap();
+        let ids = params.iter().map(|_| ui.generate_widget_id()).collect();
+        let watcher = params.source().map(Watcher::new);
+        Panel {
+            params,
+            ui,
+            ids,
+            handlers: Vec::new(),
+            visible: true,
+            watcher,
+        }
+    }
+
+    /// Watch `file` and load it whenever it changes.
+    pub fn watch<P: AsRef<std::path::Path>>(mut self, file: P) -> Self {
+        self.watcher = Some(Watcher::new(file));
+        self
+    }
+
+    /// Call `handler` when any of `names` changes, or when anything does
+    /// if `names` is empty.
+    pub fn on_change(mut self, names: &[&str], handler: fn(&mut M)) -> Self {
+        let names = names.iter().map(|name| name.to_string()).collect();
+        self.handlers.push((names, handler));
+        self
+    }
+
+    pub fn toggle(&mut self) {
+        self.visible = !self.visible;
+    }
+
+    /// Run the widgets and then the handlers of whatever changed, including
+    /// changes made through `params` since the last update. `panel` finds the
+    /// panel inside the model.
+    pub fn update(model: &mut M, panel: fn(&mut M) -> &mut Panel<M>) {
+        let handlers = panel(model).changed();
+        for handler in handlers {
+            handler(model);
+        }
+    }
+
+    fn changed(&mut self) -> Vec<fn(&mut M)> {
+        if let Some(watcher) = &mut self.watcher {
+            if watcher.changed() {
+                match self.params.load(watcher.path()) {
+                    Ok(()) => println!("reloaded {}", watcher.path().display()),
+                    Err(err) => eprintln!("failed to reload {}: {}", watcher.path().display(), err),
+                }
+            }
+        }
+        if self.visible {
+            self.widgets();
+        }
+        fired(&self.handlers, &self.params.changes())
+    }
+
+    fn widgets(&mut self) {
+        let ui = &mut self.ui.set_widgets();
+        let mut updates = Vec::new();
+        for (i, (param, &id)) in self.params.iter().zip(&self.ids).enumerate() {
+            let label = format!("{}: {}", param.name, param.value());
+            let first = i == 0;
+            match (param.kind, param.value()) {
+                (Kind::Float { min, max }, Value::Float(v)) => {
+                    let slider = widget::Slider::new(v, min, max);
+                    if let Some(v) = style(place(slider, first)).label(&label).set(id, ui) {
+                        updates.push((param.name.clone(), Value::Float(v)));
+                    }
+                }
+                (Kind::Int { min, max }, Value::Int(v)) => {
+                    let slider = widget::Slider::new(v as f32, min as f32, max as f32);
+                    if let Some(v) = style(place(slider, first)).label(&label).set(id, ui) {
+                        updates.push((param.name.clone(), Value::Float(v)));
+                    }
+                }
+                (_, Value::Bool(v)) => {
+                    let toggle = widget::Toggle::new(v);
+                    for v in style(place(toggle, first)).label(&label).set(id, ui) {
+                        updates.push((param.name.clone(), Value::Bool(v)));
+                    }
+                }
+                _ => unreachable!(),
+            }
+        }
+        for (name, value) in updates {
+            self.params.set(&name, value).unwrap();
+        }
+    }
+
+    pub fn draw(&self, app: &App, frame: &Frame) {
+        if self.visible {
+            self.ui.draw_to_frame(app, frame).unwrap();
+        }
+    }
+}
+
+/// Handlers due for `changes`, each once however many of its names changed.
+fn fired<M>(handlers: &[Handler<M>], changes: &[String]) -> Vec<fn(&mut M)> {
+    let mut fired: Vec<fn(&mut M)> = Vec::new();
+    for (names, handler) in handlers {
+        let fires = (names.is_empty() && !changes.is_empty())
+            || names.iter().any(|name| changes.contains(name));
+        if fires && !fired.iter().any(|h| *h as usize == *handler as usize) {
+            fired.push(*handler);
+        }
+    }
+    fired
+}
+
+fn place<W: Positionable>(widget: W, first: bool) -> W {
+    if first {
+        widget.top_left_with_margin(10.)
+    } else {
+        widget.down(4.)
+    }
+}
+
+fn style<'a, W>(widget: W) -> W
+where
+    W: Sizeable + Colorable + Labelable<'a> + Borderable,
+{
+    widget
+        .w_h(WIDTH, HEIGHT)
+        .rgb(0.3, 0.3, 0.3)
+        .label_rgb(1., 1., 1.)
+        .label_font_size(12)
+        .border(0.)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn count(calls: &mut usize) {
+        *calls += 1;
+    }
+
+    fn run(handlers: &[Handler<usize>], changes: &[&str]) -> usize {
+        let changes: Vec<String> = changes.iter().map(|name| name.to_string()).collect();
+        let mut calls = 0;
+        for handler in fired(handlers, &changes) {
+            handler(&mut calls);
+        }
+        calls
+    }
+
+    #[test]
+    fn handlers_fire_on_their_changes() {
+        let any: Vec<Handler<usize>> = vec![(Vec::new(), count)];
+        assert_eq!(run(&any, &[]), 0);
+        assert_eq!(run(&any, &["radius"]), 1);
+
+        let some: Vec<Handler<usize>> =
+            vec![(vec!["radius".to_string(), "seed".to_string()], count)];
+        assert_eq!(run(&some, &["speed"]), 0);
+        assert_eq!(run(&some, &["radius", "seed"]), 1);
+    }
+}
@@ -0,0 +1,192 @@
+//! Presets, the seed and every parameter value in a small TOML file.
+//!
+//! ```toml
+//! seed = 1234
+//!
+//! [params]
+//! ratio = 0.25
+//! joints = 5
+//! closed = false
+//! ```
+use crate::{Params, Value};
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl Error for ParseError {}
+
+impl From<ParseError> for io::Error {
+    fn from(err: ParseError) -> Self {
+        io::Error::new(io::ErrorKind::InvalidData, err)
+    }
+}
+
+impl Params {
+    pub fn to_toml(&self) -> String {
+        let mut out = format!("seed = {}\n\n[params]\n", self.seed);
+        for param in &self.params {
+            let value = match param.value {
+                // Debug keeps the decimal point, so floats read back as floats.
+                Value::Float(v) => format!("{:?}", v),
+                Value::Int(v) => v.to_string(),
+                Value::Bool(v) => v.to_string(),
+            };
+            out.push_str(&format!("{} = {}\n", param.name, value));
+        }
+        out
+    }
+
+    /// Apply a preset. Names this sketch doesn't know are skipped with a
+    /// warning so older presets keep loading.
+    pub fn read_toml(&mut self, toml: &str) -> Result<(), ParseError> {
+        let mut section = String::new();
+        for (i, line) in toml.lines().enumerate() {
+            let error = |message: String| ParseError {
+                line: i + 1,
+                message,
+            };
+            let line = line.split('#').next().unwrap().trim();
+            if line.is_empty() {
+                continue;
+            }
+            if line.starts_with('[') && line.ends_with(']') {
+                section = line[1..line.len() - 1].trim().to_string();
+                continue;
+            }
+            let mut parts = line.splitn(2, '=');
+            let key = parts.next().unwrap().trim();
+            let value = parts
+                .next()
+                .ok_or_else(|| error(format!("expected `key = value`, got `{}`", line)))?;
+            let value = parse_value(value.trim())
+                .ok_or_else(|| error(format!("can't read value of `{}`", key)))?;
+
+            match (section.as_str(), key, value) {
+                ("", "seed", Value::Int(seed)) if seed >= 0 => self.set_seed(seed as u64),
+                ("", "seed", _) => return Err(error("seed must be a whole number".to_string())),
+                ("params", _, _) => match self.set(key, value) {
+                    Ok(_) => {}
+                    Err(err) => eprintln!("preset line {}: {}", i + 1, err),
+                },
+                _ => eprintln!("preset line {}: `{}` ignored", i + 1, key),
+            }
+        }
+        Ok(())
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, file: P) -> io::Result<()> {
+        fs::write(file, self.to_toml())
+    }
+
+    pub fn load<P: AsRef<Path>>(&mut self, file: P) -> io::Result<()> {
+        let toml = fs::read_to_string(&file)?;
+        self.read_toml(&toml)?;
+        self.source = Some(file.as_ref().to_path_buf());
+        Ok(())
+    }
+
+    /// Save as a sidecar of `image`, same name with a `.toml` extension.
+    pub fn save_beside<P: AsRef<Path>>(&self, image: P) -> io::Result<PathBuf> {
+        let file = image.as_ref().with_extension("toml");
+        self.save(&file)?;
+        Ok(file)
+    }
+
+    /// Apply `--preset FILE`, `--seed N` and `--set name=value` from the
+    /// command line, later flags win. A preset that can't be read is
+    /// reported and the defaults are kept.
+    pub fn args(mut self) -> Self {
+        let mut args = std::env::args().skip(1);
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--preset" => {
+                    let file = args.next().unwrap_or_default();
+                    if let Err(err) = self.load(&file) {
+                        eprintln!("failed to load {}: {}", file, err);
+                    }
+                }
+                "--set" => {
+                    let assignment = args.next().unwrap_or_default();
+                    match crate::cli::parse_assignment(&assignment) {
+                        Some((name, value)) => {
+                            if let Err(err) = self.set(&name, value) {
+                                eprintln!("{}", err);
+                            }
+                        }
+                        None => eprintln!("expected `name=value`, got `{}`", assignment),
+                    }
+                }
+                "--seed" => {
+                    let seed = args.next().unwrap_or_default();
+                    match seed.parse() {
+                        Ok(seed) => self.set_seed(seed),
+                        Err(_) => eprintln!("not a seed: {}", seed),
+                    }
+                }
+                _ => {}
+            }
+        }
+        self
+    }
+}
+
+pub(crate) fn parse_value(value: &str) -> Option<Value> {
+    match value {
+        "true" => Some(Value::Bool(true)),
+        "false" => Some(Value::Bool(false)),
+        _ if value.contains(&['.', 'e', 'E'][..]) => value.parse().ok().map(Value::Float),
+        _ => value.parse().ok().map(Value::Int),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn params() -> Params {
+        Params::new()
+            .float("ratio", 0.25, 0.0..=0.5)
+            .int("joints", 5, 1..=8)
+            .toggle("closed", false)
+    }
+
+    #[test]
+    fn preset_round_trips() {
+        let mut saved = params();
+        saved.set("ratio", Value::Float(0.1)).unwrap();
+        saved.set("closed", Value::Bool(true)).unwrap();
+        saved.set_seed(42);
+
+        let mut loaded = params();
+        loaded.read_toml(&saved.to_toml()).unwrap();
+        assert_eq!(loaded.seed(), 42);
+        assert!(loaded.iter().eq(saved.iter()));
+    }
+
+    #[test]
+    fn unknown_names_are_skipped() {
+        let mut params = params();
+        let toml = "# old preset\nseed = 7\n\n[params]\nspeed = 1.5\njoints = 3 # fewer\n";
+        params.read_toml(toml).unwrap();
+        assert_eq!((params.seed(), params.usize("joints")), (7, 3));
+
+        let err = params
+            .read_toml("seed = 1\n[params]\njoints\n")
+            .unwrap_err();
+        assert_eq!(err.line, 3);
+    }
+}
@@ -0,0 +1,238 @@
+//! Contact sheets, one sketch drawn in a grid with consecutive seeds or a
+//! parameter swept along each axis.
+use crate::{cli, ParamError, Params, Value};
+use nannou::prelude::*;
+use painter::{Painter, Viewport};
+
+const GAP: f32 = 8.;
+const LABEL: f32 = 18.;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Axis {
+    /// Consecutive seeds.
+    Seed,
+    /// `name` evenly from `from` to `to`, both included.
+    Param { name: String, from: f32, to: f32 },
+}
+
+impl Axis {
+    /// `seed` or `name=from..to`.
+    pub fn parse(axis: &str) -> Option<Axis> {
+        if axis == "seed" {
+            return Some(Axis::Seed);
+        }
+        let mut parts = axis.splitn(2, '=');
+        let name = parts.next()?.trim().to_string();
+        let mut range = parts.next()?.splitn(2, "..");
+        let from = range.next()?.trim().parse().ok()?;
+        let to = range.next()?.trim_start_matches('=').trim().parse().ok()?;
+        Some(Axis::Param { name, from, to })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sheet {
+    pub columns: usize,
+    pub rows: usize,
+    pub x: Axis,
+    pub y: Axis,
+}
+
+impl Sheet {
+    /// Consecutive seeds row by row.
+    pub fn new(columns: usize, rows: usize) -> Self {
+        Sheet {
+            columns: columns.max(1),
+            rows: rows.max(1),
+            x: Axis::Seed,
+            y: Axis::Seed,
+        }
+    }
+
+    pub fn x(mut self, axis: Axis) -> Self {
+        self.x = axis;
+        self
+    }
+
+    pub fn y(mut self, axis: Axis) -> Self {
+        self.y = axis;
+        self
+    }
+
+    /// `--grid CxR` with optional `--x AXIS` and `--y AXIS`, see
+    /// `Axis::parse`. `None` without `--grid`.
+    pub fn from_args() -> Option<Self> {
+        match Self::parse(std::env::args().skip(1)) {
+            Ok(sheet) => sheet,
+            Err(err) => {
+                eprintln!("{}", err);
+                None
+            }
+        }
+    }
+
+    pub fn parse<I: IntoIterator<Item = String>>(args: I) -> Result<Option<Self>, String> {
+        let (mut grid, mut x, mut y) = (None, Axis::Seed, Axis::Seed);
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            if arg != "--grid" && arg != "--x" && arg != "--y" {
+                continue;
+            }
+            let value = args.next().unwrap_or_default();
+            let axis = || Axis::parse(&value).ok_or(format!("not an axis: {}", value));
+            match arg.as_str() {
+                "--grid" => {
+                    let size = cli::parse_size(&value).ok_or(format!("not a grid: {}", value))?;
+                    grid = Some(size);
+                }
+                "--x" => x = axis()?,
+                _ => y = axis()?,
+            }
+        }
+        Ok(grid.map(|(columns, rows)| Sheet::new(columns as usize, rows as usize).x(x).y(y)))
+    }
+
+    pub fn len(&self) -> usize {
+        self.columns * self.rows
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Parameters of every cell, row by row from the top left.
+    pub fn cells(&self, base: &Params) -> Result<Vec<Params>, ParamError> {
+        let mut cells = Vec::with_capacity(self.len());
+        for row in 0..self.rows {
+            for column in 0..self.columns {
+                let mut cell = base.clone();
+                let seed = match (&self.x, &self.y) {
+                    (Axis::Seed, Axis::Seed) => (row * self.columns + column) as u64,
+                    (Axis::Seed, _) => column as u64,
+                    (_, Axis::Seed) => row as u64,
+                    _ => 0,
+                };
+                cell.set_seed(base.seed() + seed);
+                sweep(&mut cell, &self.x, column, self.columns)?;
+                sweep(&mut cell, &self.y, row, self.rows)?;
+                cell.changes();
+                cells.push(cell);
+            }
+        }
+        Ok(cells)
+    }
+
+    /// Seed and swept values of a cell.
+    pub fn label(&self, cell: &Params) -> String {
+        let mut label = format!("seed {}", cell.seed());
+        for axis in &[&self.x, &self.y] {
+            if let Axis::Param { name, .. } = axis {
+                if let Some(param) = cell.get(name) {
+                    label.push_str(&format!("  {} {}", name, param.value()));
+                }
+            }
+        }
+        label
+    }
+
+    /// Where the thumbnail of cell `i` goes in `area`, its label sits below.
+    pub fn cell_rect(&self, area: Rect, i: usize) -> Rect {
+        let (column, row) = ((i % self.columns) as f32, (i / self.columns) as f32);
+        let w = (area.w() - GAP * (self.columns as f32 + 1.)) / self.columns as f32;
+        let h = (area.h() - GAP * (self.rows as f32 + 1.)) / self.rows as f32 - LABEL;
+        let left = area.left() + GAP + column * (w + GAP);
+        let top = area.top() - GAP - row * (h + LABEL + GAP);
+        Rect::from_x_y_w_h(left + w / 2., top - h / 2., w, h)
+    }
+
+    /// Cell under `point`, label included.
+    pub fn cell_at(&self, area: Rect, point: Point2) -> Option<usize> {
+        (0..self.len()).find(|&i| {
+            let cell = self.cell_rect(area, i);
+            let cell =
+                Rect::from_x_y_w_h(cell.x(), cell.y() - LABEL / 2., cell.w(), cell.h() + LABEL);
+            cell.contains(point)
+        })
+    }
+
+    /// Pixel size of a sheet whose thumbnails are `thumb` wide and keep the
+    /// aspect of `source`.
+    pub fn size(&self, source: Rect, thumb: f32) -> (u32, u32) {
+        let h = thumb * source.h() / source.w();
+        let width = self.columns as f32 * (thumb + GAP) + GAP;
+        let height = self.rows as f32 * (h + LABEL + GAP) + GAP;
+        (width.ceil() as u32, height.ceil() as u32)
+    }
+
+    /// Draw all cells into `area`. `draw` paints cell `i` as if `source` was
+    /// its whole window.
+    pub fn paint<P, F>(&self, painter: &P, area: Rect, source: Rect, cells: &[Params], mut draw: F)
+    where
+        P: Painter,
+        F: FnMut(&Viewport<P>, usize),
+    {
+        for (i, cell) in cells.iter().enumerate().take(self.len()) {
+            let rect = self.cell_rect(area, i);
+            draw(&Viewport::new(painter, source, rect), i);
+            let label = Rect::from_x_y_w_h(rect.x(), rect.bottom() - LABEL / 2., rect.w(), LABEL);
+            painter.text(&self.label(cell), label, 11, Srgba::new(0.2, 0.2, 0.2, 1.));
+        }
+    }
+}
+
+fn sweep(cell: &mut Params, axis: &Axis, step: usize, steps: usize) -> Result<(), ParamError> {
+    if let Axis::Param { name, from, to } = axis {
+        let t = if steps > 1 {
+            step as f32 / (steps - 1) as f32
+        } else {
+            0.
+        };
+        cell.set(name, Value::Float(from + (to - from) * t))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn args(args: &str) -> Vec<String> {
+        args.split_whitespace().map(String::from).collect()
+    }
+
+    #[test]
+    fn cells_sweep_seeds_and_params() {
+        let mut base = Params::new().int("layers", 40, 1..=100);
+        base.set_seed(10);
+
+        let sheet = Sheet::parse(args("--grid 3x2 --x layers=10..30"))
+            .unwrap()
+            .unwrap();
+        let cells = sheet.cells(&base).unwrap();
+        let seeds: Vec<u64> = cells.iter().map(|c| c.seed()).collect();
+        let layers: Vec<i64> = cells.iter().map(|c| c.i64("layers")).collect();
+        assert_eq!(seeds, vec![10, 10, 10, 11, 11, 11]);
+        assert_eq!(layers, vec![10, 20, 30, 10, 20, 30]);
+        assert_eq!(sheet.label(&cells[4]), "seed 11  layers 20");
+
+        let seeds = Sheet::new(2, 2).cells(&base).unwrap();
+        assert_eq!(seeds[3].seed(), 13);
+        assert!(Sheet::new(2, 2)
+            .x(Axis::parse("size=1..2").unwrap())
+            .cells(&base)
+            .is_err());
+        assert_eq!(Sheet::parse(args("--seed 3")), Ok(None));
+    }
+
+    #[test]
+    fn cells_are_found_by_position() {
+        let sheet = Sheet::new(3, 2);
+        let area = Rect::from_w_h(300., 200.);
+        for i in 0..sheet.len() {
+            let rect = sheet.cell_rect(area, i);
+            assert_eq!(sheet.cell_at(area, rect.xy()), Some(i));
+            assert!(area.contains(pt2(rect.left(), rect.bottom() - LABEL)));
+        }
+        assert_eq!(sheet.cell_at(area, pt2(-149., 99.)), None);
+    }
+}
@@ -0,0 +1,77 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+
+/// Polls a file for changes by its modification time and size.
+///
+/// Polling keeps it working with editors that save by renaming a new file
+/// over the old one. A file that is missing for a moment is not a change.
+#[derive(Debug, Clone)]
+pub struct Watcher {
+    path: PathBuf,
+    stamp: Option<(SystemTime, u64)>,
+    interval: Duration,
+    checked: Instant,
+}
+
+impl Watcher {
+    pub fn new<P: AsRef<Path>>(path: P) -> Self {
+        let path = path.as_ref().to_path_buf();
+        Watcher {
+            stamp: stamp(&path),
+            path,
+            interval: Duration::from_millis(250),
+            checked: Instant::now(),
+        }
+    }
+
+    /// Least time between two looks at the file, a quarter second by default.
+    pub fn interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Whether the file changed since the last call that said so.
+    pub fn changed(&mut self) -> bool {
+        if self.checked.elapsed() < self.interval {
+            return false;
+        }
+        self.checked = Instant::now();
+        match stamp(&self.path) {
+            Some(stamp) if Some(stamp) != self.stamp => {
+                self.stamp = Some(stamp);
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+fn stamp(path: &Path) -> Option<(SystemTime, u64)> {
+    let metadata = fs::metadata(path).ok()?;
+    Some((metadata.modified().ok()?, metadata.len()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn notices_rewrites() {
+        let file = std::env::temp_dir().join(format!("params-watch-{}.toml", std::process::id()));
+        fs::write(&file, "seed = 1\n").unwrap();
+        let mut watcher = Watcher::new(&file).interval(Duration::from_secs(0));
+        assert!(!watcher.changed());
+
+        fs::write(&file, "seed = 12\n").unwrap();
+        assert!(watcher.changed());
+        assert!(!watcher.changed());
+
+        fs::remove_file(&file).unwrap();
+        assert!(!watcher.changed());
+    }
+}
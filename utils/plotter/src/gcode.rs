@@ -0,0 +1,115 @@
+//! G-code for pen plotters, laser cutters and other CNC machines.
+use crate::machine::{Bed, OutOfBounds};
+use crate::svg::layers;
+use crate::Path;
+use nannou::prelude::*;
+use std::fmt::Write as _;
+use std::fs;
+use std::io;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GcodeOptions {
+    pub bed: Bed,
+    /// Drawing speed in mm/min.
+    pub feed_rate: f32,
+    /// Pen-up speed in mm/min.
+    pub travel_rate: f32,
+    /// Lines that lift the pen or switch the laser off.
+    pub pen_up: String,
+    /// Lines that lower the pen or switch the laser on.
+    pub pen_down: String,
+    /// Stop with `M0` before every layer after the first to change pens.
+    pub pause_between_layers: bool,
+}
+
+impl Default for GcodeOptions {
+    fn default() -> Self {
+        GcodeOptions {
+            bed: Bed::new(300., 200.),
+            feed_rate: 1500.,
+            travel_rate: 3000.,
+            pen_up: "G0 Z5".to_string(),
+            pen_down: "G1 Z0 F500".to_string(),
+            pause_between_layers: true,
+        }
+    }
+}
+
+/// Absolute millimetre moves, `G0` with the pen up and `G1` with it down.
+pub fn to_gcode(paths: &[Path], area: Rect, options: &GcodeOptions) -> Result<String, OutOfBounds> {
+    let placed = options.bed.place(paths, area)?;
+
+    let mut out = String::new();
+    writeln!(out, "G21 ; millimetres").unwrap();
+    writeln!(out, "G90 ; absolute positioning").unwrap();
+    writeln!(out, "{}", options.pen_up).unwrap();
+    for (i, (color, layer)) in layers(&placed).into_iter().enumerate() {
+        if i > 0 && options.pause_between_layers {
+            writeln!(out, "{}", options.pen_up).unwrap();
+            writeln!(out, "M0 ; change pen to {}", color).unwrap();
+        } else {
+            writeln!(out, "; layer {}", color).unwrap();
+        }
+        for path in &layer {
+            let p = path.start();
+            writeln!(out, "G0 X{:.3} Y{:.3} F{}", p.x, p.y, options.travel_rate).unwrap();
+            writeln!(out, "{}", options.pen_down).unwrap();
+            for (j, p) in path.points.iter().enumerate().skip(1) {
+                write!(out, "G1 X{:.3} Y{:.3}", p.x, p.y).unwrap();
+                if j == 1 {
+                    write!(out, " F{}", options.feed_rate).unwrap();
+                }
+                out.push('\n');
+            }
+            writeln!(out, "{}", options.pen_up).unwrap();
+        }
+    }
+    writeln!(out, "G0 X0 Y0 F{}", options.travel_rate).unwrap();
+    writeln!(out, "M2").unwrap();
+
+    Ok(out)
+}
+
+pub fn save<P: AsRef<std::path::Path>>(
+    file: P,
+    paths: &[Path],
+    area: Rect,
+    options: &GcodeOptions,
+) -> io::Result<()> {
+    fs::write(file, to_gcode(paths, area, options)?)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test::square;
+
+    #[test]
+    fn gcode_moves_pen_up_between_paths() {
+        let stroke = Path::open(vec![pt2(0.5, 0.), pt2(0.5, 0.5)], square().color, 1.);
+        let paths = [square(), stroke];
+        let gcode = to_gcode(&paths, Rect::from_w_h(2., 2.), &GcodeOptions::default()).unwrap();
+        let lines: Vec<&str> = gcode.lines().collect();
+        let downs: Vec<usize> = (0..lines.len())
+            .filter(|&i| lines[i].starts_with("G1 Z0"))
+            .collect();
+        assert_eq!(downs.len(), 2);
+        // Lift, travel to the next start with G0, lower again.
+        let between = &lines[downs[0] + 1..downs[1]];
+        let lift = between.iter().position(|l| *l == "G0 Z5").unwrap();
+        assert!(between[..lift].iter().all(|l| l.starts_with("G1 X")));
+        assert_eq!(between.len(), lift + 2);
+        assert!(between[lift + 1].starts_with("G0 X"));
+        assert_eq!(gcode.lines().filter(|l| l.starts_with("G1 X")).count(), 5);
+    }
+
+    #[test]
+    fn refuses_moves_off_the_bed() {
+        let options = GcodeOptions {
+            bed: Bed::new(10., 10.).scale(10.),
+            ..GcodeOptions::default()
+        };
+        let err = to_gcode(&[square()], Rect::from_w_h(2., 2.), &options).unwrap_err();
+        assert_eq!((err.x, err.y), (-5., -5.));
+    }
+}
@@ -0,0 +1,82 @@
+//! HPGL for pen plotters, every color layer goes to its own pen slot.
+use crate::machine::{Bed, OutOfBounds};
+use crate::svg::layers;
+use crate::Path;
+use nannou::prelude::*;
+use std::fmt::Write as _;
+use std::fs;
+use std::io;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct HpglOptions {
+    pub bed: Bed,
+    /// Plotter units per millimetre, 40 on most HP compatible machines.
+    pub units_per_mm: f32,
+    /// Pen speed in cm/s, `None` leaves the plotter default.
+    pub velocity: Option<f32>,
+    /// Pen slot used for the first layer, the next layers count up from it.
+    pub first_pen: usize,
+}
+
+impl Default for HpglOptions {
+    fn default() -> Self {
+        HpglOptions {
+            bed: Bed::new(420., 297.),
+            units_per_mm: 40.,
+            velocity: None,
+            first_pen: 1,
+        }
+    }
+}
+
+/// `PU`/`PD` commands in absolute plotter units.
+pub fn to_hpgl(paths: &[Path], area: Rect, options: &HpglOptions) -> Result<String, OutOfBounds> {
+    let placed = options.bed.place(paths, area)?;
+    let unit = |v: f32| (v * options.units_per_mm).round() as i64;
+
+    let mut out = String::from("IN;PA;");
+    if let Some(velocity) = options.velocity {
+        write!(out, "VS{};", velocity).unwrap();
+    }
+    out.push('\n');
+    for (i, (_, layer)) in layers(&placed).into_iter().enumerate() {
+        writeln!(out, "SP{};", options.first_pen + i).unwrap();
+        for path in &layer {
+            let p = path.start();
+            write!(out, "PU{},{};PD", unit(p.x), unit(p.y)).unwrap();
+            for (j, p) in path.points.iter().enumerate().skip(1) {
+                let sep = if j == 1 { "" } else { "," };
+                write!(out, "{}{},{}", sep, unit(p.x), unit(p.y)).unwrap();
+            }
+            out.push_str(";\n");
+        }
+    }
+    out.push_str("PU;SP0;\n");
+
+    Ok(out)
+}
+
+pub fn save<P: AsRef<std::path::Path>>(
+    file: P,
+    paths: &[Path],
+    area: Rect,
+    options: &HpglOptions,
+) -> io::Result<()> {
+    fs::write(file, to_hpgl(paths, area, options)?)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test::square;
+
+    #[test]
+    fn hpgl_fits_drawing_to_bed() {
+        let options = HpglOptions {
+            bed: Bed::new(100., 100.).margin(0.),
+            ..HpglOptions::default()
+        };
+        let hpgl = to_hpgl(&[square()], Rect::from_w_h(2., 2.), &options).unwrap();
+        assert!(hpgl.contains("SP1;\nPU0,0;PD4000,0,4000,4000,0,4000,0,0;\n"));
+    }
+}
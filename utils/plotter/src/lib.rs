@@ -0,0 +1,185 @@
+use nannou::prelude::*;
+use painter::Painter;
+use std::cell::RefCell;
+use std::io;
+
+pub mod gcode;
+pub mod hpgl;
+pub mod machine;
+pub mod optimize;
+pub mod svg;
+
+pub use gcode::GcodeOptions;
+pub use hpgl::HpglOptions;
+pub use machine::{Bed, OutOfBounds};
+pub use optimize::{Options, Order, Report};
+pub use svg::{Page, SvgOptions, Unit};
+
+/// One pen-down stroke in sketch coordinates.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Path {
+    pub points: Vec<Point2>,
+    pub closed: bool,
+    pub color: Srgba,
+    pub weight: f32,
+}
+
+impl Path {
+    pub fn open(points: Vec<Point2>, color: Srgba, weight: f32) -> Self {
+        Path {
+            points,
+            closed: false,
+            color,
+            weight,
+        }
+    }
+
+    pub fn closed(points: Vec<Point2>, color: Srgba, weight: f32) -> Self {
+        Path {
+            points,
+            closed: true,
+            color,
+            weight,
+        }
+    }
+
+    pub fn start(&self) -> Point2 {
+        self.points[0]
+    }
+
+    /// Where the pen lifts, which is the start again for closed paths.
+    pub fn end(&self) -> Point2 {
+        if self.closed {
+            self.points[0]
+        } else {
+            self.points[self.points.len() - 1]
+        }
+    }
+
+    /// Draw the other way round, closed paths keep their start point.
+    pub fn reverse(&mut self) {
+        self.points.reverse();
+        if self.closed {
+            self.points.rotate_right(1);
+        }
+    }
+
+    /// Hex color used to split paths into pen layers.
+    pub fn layer(&self) -> String {
+        let c = |v: f32| (v.clamp(0., 1.) * 255.).round() as u8;
+        format!(
+            "#{:02x}{:02x}{:02x}",
+            c(self.color.red),
+            c(self.color.green),
+            c(self.color.blue)
+        )
+    }
+}
+
+/// Painter that records line geometry instead of drawing it.
+///
+/// Fills are recorded as their outlines, text and meshes are skipped since
+/// a pen can't do anything sensible with them.
+#[derive(Default)]
+pub struct Recorder {
+    paths: RefCell<Vec<Path>>,
+}
+
+impl Recorder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn paths(&self) -> Vec<Path> {
+        self.paths.borrow().clone()
+    }
+
+    pub fn into_paths(self) -> Vec<Path> {
+        self.paths.into_inner()
+    }
+
+    pub fn clear(&self) {
+        self.paths.borrow_mut().clear();
+    }
+
+    pub fn push(&self, path: Path) {
+        if path.points.len() > 1 {
+            self.paths.borrow_mut().push(path);
+        }
+    }
+
+    /// Replace the recorded paths with their optimised version.
+    pub fn optimize(&self, options: &Options) -> Report {
+        let paths = self.paths.replace(Vec::new());
+        let (paths, report) = optimize::optimize(paths, options);
+        self.paths.replace(paths);
+        report
+    }
+
+    /// Write everything recorded so far, `area` is the part of the sketch
+    /// that is mapped onto the page.
+    pub fn save_svg<P: AsRef<std::path::Path>>(
+        &self,
+        file: P,
+        area: Rect,
+        options: &SvgOptions,
+    ) -> io::Result<()> {
+        svg::save(file, &self.paths.borrow(), area, options)
+    }
+
+    /// Write G-code, fails without writing if a move would leave the bed.
+    pub fn save_gcode<P: AsRef<std::path::Path>>(
+        &self,
+        file: P,
+        area: Rect,
+        options: &GcodeOptions,
+    ) -> io::Result<()> {
+        gcode::save(file, &self.paths.borrow(), area, options)
+    }
+
+    /// Write HPGL, fails without writing if a move would leave the bed.
+    pub fn save_hpgl<P: AsRef<std::path::Path>>(
+        &self,
+        file: P,
+        area: Rect,
+        options: &HpglOptions,
+    ) -> io::Result<()> {
+        hpgl::save(file, &self.paths.borrow(), area, options)
+    }
+}
+
+impl Painter for Recorder {
+    fn background(&self, _color: Srgba) {}
+
+    fn line(&self, start: Point2, end: Point2, weight: f32, color: Srgba) {
+        self.push(Path::open(vec![start, end], color, weight));
+    }
+
+    fn polyline(&self, points: &[Point2], weight: f32, color: Srgba) {
+        self.push(Path::open(points.to_vec(), color, weight));
+    }
+
+    fn polygon(&self, points: &[Point2], color: Srgba) {
+        self.push(Path::closed(points.to_vec(), color, 1.));
+    }
+
+    fn ellipse(&self, center: Point2, w: f32, h: f32, color: Srgba) {
+        let points = canvas::raster::ellipse(center, w / 2., h / 2.);
+        self.push(Path::closed(points, color, 1.));
+    }
+
+    fn mesh(&self, _vertices: &[(Point2, Srgba)]) {}
+
+    fn text(&self, _text: &str, _rect: Rect, _size: u32, _color: Srgba) {}
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Closed 2x2 square around the origin, shared by the exporter tests.
+    pub(crate) fn square() -> Path {
+        let points = vec![pt2(-1., -1.), pt2(1., -1.), pt2(1., 1.), pt2(-1., 1.)];
+        Path::closed(points, Srgba::new(0., 0., 0., 1.), 1.)
+    }
+}
@@ -0,0 +1,114 @@
+//! What G-code and HPGL output share: the machine bed and the mapping of
+//! sketch coordinates onto it.
+use crate::Path;
+use nannou::prelude::*;
+use std::error::Error;
+use std::fmt;
+use std::io;
+
+/// Working area of a plotter or cutter, in millimetres with the origin in
+/// the lower left corner.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Bed {
+    pub width: f32,
+    pub height: f32,
+    /// Empty border kept when the drawing is fitted to the bed.
+    pub margin: f32,
+    /// Millimetres per sketch unit, `None` fits the drawing to the bed.
+    pub scale: Option<f32>,
+}
+
+impl Bed {
+    pub fn new(width: f32, height: f32) -> Self {
+        Bed {
+            width,
+            height,
+            margin: 10.,
+            scale: None,
+        }
+    }
+
+    pub fn margin(mut self, margin: f32) -> Self {
+        self.margin = margin;
+        self
+    }
+
+    pub fn scale(mut self, scale: f32) -> Self {
+        self.scale = Some(scale);
+        self
+    }
+
+    /// Map `area` of the sketch onto the middle of the bed. y points up
+    /// on both sides so nothing gets flipped.
+    pub fn transform(&self, area: Rect) -> impl Fn(Point2) -> Point2 {
+        let scale = self.scale.unwrap_or_else(|| {
+            ((self.width - 2. * self.margin) / area.w())
+                .min((self.height - 2. * self.margin) / area.h())
+        });
+        let center = pt2(self.width / 2., self.height / 2.);
+        let origin = pt2(area.x(), area.y());
+        move |p| center + (p - origin) * scale
+    }
+
+    pub fn contains(&self, p: Point2) -> bool {
+        const EPSILON: f32 = 1e-3;
+        p.x >= -EPSILON
+            && p.y >= -EPSILON
+            && p.x <= self.width + EPSILON
+            && p.y <= self.height + EPSILON
+    }
+
+    /// Every path mapped onto the bed, or the first point that falls off it.
+    pub fn place(&self, paths: &[Path], area: Rect) -> Result<Vec<Path>, OutOfBounds> {
+        let transform = self.transform(area);
+        paths
+            .iter()
+            .map(|path| {
+                let mut path = path.clone();
+                for p in &mut path.points {
+                    *p = transform(*p);
+                    if !self.contains(*p) {
+                        return Err(OutOfBounds {
+                            x: p.x,
+                            y: p.y,
+                            width: self.width,
+                            height: self.height,
+                        });
+                    }
+                }
+                if path.closed {
+                    path.points.push(path.points[0]);
+                    path.closed = false;
+                }
+                Ok(path)
+            })
+            .collect()
+    }
+}
+
+/// A move would leave the machine area, nothing is written in that case.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OutOfBounds {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+impl fmt::Display for OutOfBounds {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "move to ({:.2}, {:.2}) mm is outside the {} x {} mm bed",
+            self.x, self.y, self.width, self.height
+        )
+    }
+}
+
+impl Error for OutOfBounds {}
+
+impl From<OutOfBounds> for io::Error {
+    fn from(err: OutOfBounds) -> Self {
+        io::Error::new(io::ErrorKind::InvalidInput, err)
+    }
+}
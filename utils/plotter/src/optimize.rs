@@ -0,0 +1,593 @@
+//! Reordering and joining paths so the pen spends less time in the air.
+use crate::Path;
+use nannou::prelude::*;
+use std::fmt;
+
+/// How far ahead 2-opt looks for a block to reverse, keeps a pass linear.
+const NEIGHBOURHOOD: usize = 64;
+
+/// Segments whose directions differ by less than this (radians) count as
+/// parallel when looking for overlaps.
+const ANGLE_EPSILON: f32 = 1e-3;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Order {
+    /// Draw paths in the order they were recorded.
+    Keep,
+    /// Always move to the closest remaining path end.
+    Greedy,
+    /// Greedy, then up to this many passes of 2-opt.
+    TwoOpt(usize),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Options {
+    pub order: Order,
+    /// Join open paths whose ends are closer than this, in sketch units.
+    pub merge: Option<f32>,
+    /// Collapse segments that lie on top of each other within this distance.
+    pub dedup: Option<f32>,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            order: Order::TwoOpt(4),
+            merge: Some(0.1),
+            dedup: Some(0.1),
+        }
+    }
+}
+
+/// Before and after numbers of an optimisation pass.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Report {
+    pub paths_before: usize,
+    pub paths_after: usize,
+    pub travel_before: f32,
+    pub travel_after: f32,
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let saved = if self.travel_before > 0. {
+            100. * (1. - self.travel_after / self.travel_before)
+        } else {
+            0.
+        };
+        write!(
+            f,
+            "paths: {} -> {}, pen-up travel: {:.1} -> {:.1} ({:.0}% less)",
+            self.paths_before, self.paths_after, self.travel_before, self.travel_after, saved
+        )
+    }
+}
+
+/// Run every step enabled in `options` on each pen layer separately, the
+/// layers keep the order in which their colors first appear.
+pub fn optimize(paths: Vec<Path>, options: &Options) -> (Vec<Path>, Report) {
+    let paths_before = paths.len();
+    let travel_before = travel(&paths);
+
+    let mut optimized = Vec::with_capacity(paths.len());
+    for (_, mut layer) in crate::svg::layers(&paths) {
+        if let Some(tolerance) = options.dedup {
+            layer = dedup(layer, tolerance);
+        }
+        if let Some(tolerance) = options.merge {
+            layer = merge(layer, tolerance);
+        }
+        layer = match options.order {
+            Order::Keep => layer,
+            Order::Greedy => order_greedy(layer),
+            Order::TwoOpt(passes) => two_opt(order_greedy(layer), passes),
+        };
+        optimized.extend(layer);
+    }
+
+    let report = Report {
+        paths_before,
+        paths_after: optimized.len(),
+        travel_before,
+        travel_after: travel(&optimized),
+    };
+    (optimized, report)
+}
+
+/// Total pen-up distance when drawing `paths` in order, starting at the origin.
+pub fn travel(paths: &[Path]) -> f32 {
+    let mut pen = pt2(0., 0.);
+    let mut total = 0.;
+    for path in paths {
+        total += pen.distance(path.start());
+        pen = path.end();
+    }
+    total
+}
+
+/// Greedy nearest neighbour ordering, paths are flipped when their far end
+/// is closer to the pen.
+pub fn order_greedy(paths: Vec<Path>) -> Vec<Path> {
+    let mut grid = Grid::new(endpoints(&paths), 0.);
+    let mut slots: Vec<Option<Path>> = paths.into_iter().map(Some).collect();
+    let mut ordered = Vec::with_capacity(slots.len());
+    let mut pen = pt2(0., 0.);
+    while let Some((k, _)) = grid.nearest(pen, f32::MAX, |_| true) {
+        grid.remove(k & !1);
+        grid.remove(k | 1);
+        let mut path = slots[k / 2].take().expect("path is still in the grid");
+        if k % 2 == 1 {
+            path.reverse();
+        }
+        pen = path.end();
+        ordered.push(path);
+    }
+    ordered
+}
+
+/// Improve an ordering by reversing runs of paths, together with the
+/// direction of each path in the run, wherever that shortens the travel.
+pub fn two_opt(mut paths: Vec<Path>, passes: usize) -> Vec<Path> {
+    let n = paths.len();
+    for _ in 0..passes {
+        let mut improved = false;
+        for i in 0..n {
+            let before = if i == 0 {
+                pt2(0., 0.)
+            } else {
+                paths[i - 1].end()
+            };
+            for j in i..n.min(i + NEIGHBOURHOOD) {
+                let after = paths.get(j + 1).map(Path::start);
+                let old = before.distance(paths[i].start())
+                    + after.map(|a| paths[j].end().distance(a)).unwrap_or(0.);
+                let new = before.distance(paths[j].end())
+                    + after.map(|a| paths[i].start().distance(a)).unwrap_or(0.);
+                if new < old - 1e-4 {
+                    paths[i..=j].reverse();
+                    paths[i..=j].iter_mut().for_each(Path::reverse);
+                    improved = true;
+                }
+            }
+        }
+        if !improved {
+            break;
+        }
+    }
+    paths
+}
+
+/// Join open paths of the same color whose ends lie within `tolerance`.
+///
+/// Chains that come back to where they started become closed paths.
+pub fn merge(paths: Vec<Path>, tolerance: f32) -> Vec<Path> {
+    let ends = endpoints(&paths);
+    let mut grid = Grid::new(ends.clone(), tolerance);
+    let mut link: Vec<Option<usize>> = vec![None; ends.len()];
+    for k in 0..ends.len() {
+        if paths[k / 2].closed || link[k].is_some() {
+            continue;
+        }
+        let found = grid.nearest(ends[k], tolerance, |m| {
+            m / 2 != k / 2
+                && !paths[m / 2].closed
+                && link[m].is_none()
+                && paths[m / 2].color == paths[k / 2].color
+        });
+        if let Some((m, _)) = found {
+            link[k] = Some(m);
+            link[m] = Some(k);
+            grid.remove(k);
+            grid.remove(m);
+        }
+    }
+
+    let mut slots: Vec<Option<Path>> = paths.into_iter().map(Some).collect();
+    let mut merged = Vec::with_capacity(slots.len());
+    // Chains with a loose end first, whatever is left over are loops.
+    for i in 0..slots.len() {
+        let head = match &slots[i] {
+            Some(path) if path.closed => 2 * i,
+            Some(_) if link[2 * i].is_none() => 2 * i,
+            Some(_) if link[2 * i + 1].is_none() => 2 * i + 1,
+            _ => continue,
+        };
+        merged.push(chain(&mut slots, &link, head));
+    }
+    for i in 0..slots.len() {
+        if slots[i].is_some() {
+            merged.push(chain(&mut slots, &link, 2 * i));
+        }
+    }
+    merged
+}
+
+/// Follow links from endpoint `head` and concatenate the paths on the way.
+fn chain(slots: &mut [Option<Path>], link: &[Option<usize>], head: usize) -> Path {
+    let mut path = slots[head / 2].take().expect("chain starts at a free path");
+    if head % 2 == 1 {
+        path.reverse();
+    }
+    if path.closed {
+        return path;
+    }
+    let mut exit = head ^ 1;
+    while let Some(m) = link[exit] {
+        let mut next = match slots[m / 2].take() {
+            Some(next) => next,
+            None => {
+                // Back at the head, the joint point is already the first one.
+                path.points.pop();
+                path.closed = true;
+                break;
+            }
+        };
+        if m % 2 == 1 {
+            next.reverse();
+        }
+        path.weight = path.weight.max(next.weight);
+        path.points.extend(next.points.into_iter().skip(1));
+        exit = m ^ 1;
+    }
+    path
+}
+
+/// Remove paths that are drawn twice and fuse straight segments that
+/// overlap along the same line.
+///
+/// All paths are assumed to go to the same pen, `optimize` runs this per
+/// layer.
+pub fn dedup(paths: Vec<Path>, tolerance: f32) -> Vec<Path> {
+    let mut kept: Vec<Path> = Vec::new();
+    let mut lines = Vec::new();
+    for path in paths {
+        if !path.closed && path.points.len() == 2 && path.start() != path.end() {
+            lines.push(Line::new(path));
+        } else if !kept.iter().any(|k| same(k, &path, tolerance)) {
+            kept.push(path);
+        }
+    }
+
+    // Bundle lines that are parallel and close to the first one in the
+    // bundle, then merge their intervals.
+    lines.sort_by(|a, b| a.angle.partial_cmp(&b.angle).unwrap());
+    let mut start = 0;
+    while start < lines.len() {
+        let mut end = start + 1;
+        while end < lines.len() && lines[end].angle - lines[start].angle <= ANGLE_EPSILON {
+            end += 1;
+        }
+        let bundle = &mut lines[start..end];
+        bundle.sort_by(|a, b| a.offset.partial_cmp(&b.offset).unwrap());
+        let mut first = 0;
+        while first < bundle.len() {
+            let mut last = first + 1;
+            while last < bundle.len() && bundle[last].offset - bundle[first].offset <= tolerance {
+                last += 1;
+            }
+            fuse(&mut bundle[first..last], tolerance, &mut kept);
+            first = last;
+        }
+        start = end;
+    }
+    kept
+}
+
+/// A straight segment in the frame of its own direction.
+struct Line {
+    angle: f32,
+    offset: f32,
+    from: f32,
+    to: f32,
+    direction: Vector2,
+    color: Srgba,
+    weight: f32,
+}
+
+impl Line {
+    fn new(path: Path) -> Self {
+        let (mut a, mut b) = (path.start(), path.end());
+        if b.y < a.y || (b.y == a.y && b.x < a.x) {
+            std::mem::swap(&mut a, &mut b);
+        }
+        let mut direction = (b - a).normalize();
+        // Angles near π are folded to just below 0, so a near-horizontal
+        // line sloping down sorts next to one sloping up.
+        if direction.y.atan2(direction.x) > PI - ANGLE_EPSILON {
+            std::mem::swap(&mut a, &mut b);
+            direction = -direction;
+        }
+        let normal = vec2(-direction.y, direction.x);
+        Line {
+            angle: direction.y.atan2(direction.x),
+            offset: normal.dot(a),
+            from: direction.dot(a),
+            to: direction.dot(b),
+            direction,
+            color: path.color,
+            weight: path.weight,
+        }
+    }
+}
+
+/// Merge the overlapping intervals of parallel lines sitting on one line.
+fn fuse(lines: &mut [Line], tolerance: f32, out: &mut Vec<Path>) {
+    lines.sort_by(|a, b| a.from.partial_cmp(&b.from).unwrap());
+    let direction = lines[0].direction;
+    let normal = vec2(-direction.y, direction.x) * lines[0].offset;
+    let point = |t: f32| pt2(normal.x + direction.x * t, normal.y + direction.y * t);
+    let (mut from, mut to, mut weight) = (lines[0].from, lines[0].to, lines[0].weight);
+    for line in &lines[1..] {
+        if line.from <= to + tolerance {
+            to = to.max(line.to);
+            weight = weight.max(line.weight);
+        } else {
+            out.push(Path::open(
+                vec![point(from), point(to)],
+                lines[0].color,
+                weight,
+            ));
+            from = line.from;
+            to = line.to;
+            weight = line.weight;
+        }
+    }
+    out.push(Path::open(
+        vec![point(from), point(to)],
+        lines[0].color,
+        weight,
+    ));
+}
+
+/// Whether two paths trace the same points, in either direction.
+fn same(a: &Path, b: &Path, tolerance: f32) -> bool {
+    let close = |p: &Point2, q: &Point2| p.distance(*q) <= tolerance;
+    a.closed == b.closed
+        && a.points.len() == b.points.len()
+        && (a.points.iter().zip(&b.points).all(|(p, q)| close(p, q))
+            || a.points
+                .iter()
+                .zip(b.points.iter().rev())
+                .all(|(p, q)| close(p, q)))
+}
+
+/// Start and end of every path, path `i` owns indices `2i` and `2i + 1`.
+fn endpoints(paths: &[Path]) -> Vec<Point2> {
+    paths
+        .iter()
+        .flat_map(|p| vec![p.start(), p.end()])
+        .collect()
+}
+
+/// Uniform grid over points for nearest neighbour queries.
+struct Grid {
+    points: Vec<Point2>,
+    cells: Vec<Vec<usize>>,
+    min: Point2,
+    size: f32,
+    cols: isize,
+    rows: isize,
+}
+
+impl Grid {
+    /// Cells are at least `size` wide and grown until there are about two
+    /// per point.
+    fn new(points: Vec<Point2>, size: f32) -> Self {
+        let inf = pt2(f32::MAX, f32::MAX);
+        let min = points
+            .iter()
+            .fold(inf, |m, p| pt2(m.x.min(p.x), m.y.min(p.y)));
+        let max = points
+            .iter()
+            .fold(-inf, |m, p| pt2(m.x.max(p.x), m.y.max(p.y)));
+        let extent = if points.is_empty() {
+            vec2(0., 0.)
+        } else {
+            max - min
+        };
+        let mut size = size.max(1e-3);
+        while (extent.x / size + 1.) * (extent.y / size + 1.) > 2. * points.len() as f32 + 16. {
+            size *= 2.;
+        }
+        let cols = (extent.x / size) as isize + 1;
+        let rows = (extent.y / size) as isize + 1;
+        let mut grid = Grid {
+            points: Vec::new(),
+            cells: vec![Vec::new(); (cols * rows) as usize],
+            min,
+            size,
+            cols,
+            rows,
+        };
+        for (i, &p) in points.iter().enumerate() {
+            let (x, y) = grid.cell(p);
+            grid.cells[(y * cols + x) as usize].push(i);
+        }
+        grid.points = points;
+        grid
+    }
+
+    fn cell(&self, p: Point2) -> (isize, isize) {
+        (
+            ((p.x - self.min.x) / self.size).floor() as isize,
+            ((p.y - self.min.y) / self.size).floor() as isize,
+        )
+    }
+
+    fn remove(&mut self, i: usize) {
+        let (x, y) = self.cell(self.points[i]);
+        self.cells[(y * self.cols + x) as usize].retain(|&j| j != i);
+    }
+
+    /// Closest point within `max` of `p` that passes `accept`, searching
+    /// rings of cells outwards until nothing closer can turn up.
+    fn nearest<F>(&self, p: Point2, max: f32, accept: F) -> Option<(usize, f32)>
+    where
+        F: Fn(usize) -> bool,
+    {
+        if self.points.is_empty() {
+            return None;
+        }
+        let (cx, cy) = self.cell(p);
+        let mut best: Option<(usize, f32)> = None;
+        // Rings closer than this lie entirely outside the grid.
+        let outside = |c: isize, n: isize| (-c).max(c - (n - 1)).max(0);
+        let first = outside(cx, self.cols).max(outside(cy, self.rows));
+        let mut ring = Vec::new();
+        for r in first.. {
+            self.ring(cx, cy, r, &mut ring);
+            for &(x, y) in &ring {
+                for &i in &self.cells[(y * self.cols + x) as usize] {
+                    let d = p.distance(self.points[i]);
+                    let closer = match best {
+                        Some((_, b)) => d < b,
+                        None => true,
+                    };
+                    if d <= max && closer && accept(i) {
+                        best = Some((i, d));
+                    }
+                }
+            }
+            let reach = r as f32 * self.size;
+            let covered = cx - r <= 0 && cy - r <= 0 && cx + r >= self.cols && cy + r >= self.rows;
+            if covered || reach > max || best.map(|(_, d)| d <= reach).unwrap_or(false) {
+                break;
+            }
+        }
+        best
+    }
+
+    /// Cells at Chebyshev distance `r` from `(cx, cy)` that are inside the
+    /// grid, into `ring`.
+    fn ring(&self, cx: isize, cy: isize, r: isize, ring: &mut Vec<(isize, isize)>) {
+        ring.clear();
+        let xs = (cx - r).max(0)..=(cx + r).min(self.cols - 1);
+        for &y in &[cy - r, cy + r] {
+            if y >= 0 && y < self.rows {
+                ring.extend(xs.clone().map(|x| (x, y)));
+            }
+            if r == 0 {
+                break;
+            }
+        }
+        let ys = (cy - r + 1).max(0)..=(cy + r - 1).min(self.rows - 1);
+        for &x in &[cx - r, cx + r] {
+            if r > 0 && x >= 0 && x < self.cols {
+                ring.extend(ys.clone().map(|y| (x, y)));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn line(a: Point2, b: Point2) -> Path {
+        Path::open(vec![a, b], Srgba::new(0., 0., 0., 1.), 1.)
+    }
+
+    #[test]
+    fn greedy_order_cuts_travel() {
+        let paths = vec![
+            line(pt2(10., 0.), pt2(11., 0.)),
+            line(pt2(2., 0.), pt2(1., 0.)),
+            line(pt2(5., 0.), pt2(6., 0.)),
+        ];
+        let ordered = order_greedy(paths.clone());
+        assert!(travel(&ordered) < travel(&paths));
+        assert_eq!(ordered[0].start(), pt2(1., 0.));
+        assert_eq!(ordered[2].end(), pt2(11., 0.));
+    }
+
+    #[test]
+    fn greedy_order_reaches_far_away_paths() {
+        // Tiny cells far from the pen, where it starts.
+        let far = pt2(5e3, -5e3);
+        let paths = vec![
+            line(far + vec2(0.01, 0.), far + vec2(0.02, 0.)),
+            line(far, far + vec2(0., 0.001)),
+        ];
+        let ordered = order_greedy(paths);
+        assert_eq!(ordered.len(), 2);
+        // The short one, entered from its end nearer the origin.
+        assert_eq!(ordered[0].end(), far);
+    }
+
+    #[test]
+    fn two_opt_untangles_crossing() {
+        let paths = vec![
+            line(pt2(0., 1.), pt2(0., 2.)),
+            line(pt2(10., 2.), pt2(10., 3.)),
+            line(pt2(0., 3.), pt2(0., 4.)),
+            line(pt2(10., 4.), pt2(10., 5.)),
+        ];
+        let better = two_opt(paths.clone(), 4);
+        assert!(travel(&better) < travel(&paths));
+        assert_eq!(better.len(), paths.len());
+    }
+
+    #[test]
+    fn merge_joins_touching_ends() {
+        let paths = vec![
+            line(pt2(0., 0.), pt2(1., 0.)),
+            line(pt2(2., 0.), pt2(1.01, 0.)),
+            line(pt2(5., 5.), pt2(6., 5.)),
+        ];
+        let merged = merge(paths, 0.05);
+        assert_eq!(merged.len(), 2);
+        let joined = merged.iter().find(|p| p.points.len() == 3).unwrap();
+        assert_eq!(joined.start(), pt2(0., 0.));
+        assert_eq!(joined.end(), pt2(2., 0.));
+    }
+
+    #[test]
+    fn merge_closes_loops() {
+        let paths = vec![
+            line(pt2(0., 0.), pt2(1., 0.)),
+            line(pt2(1., 1.), pt2(1., 0.)),
+            line(pt2(0., 0.), pt2(1., 1.)),
+        ];
+        let merged = merge(paths, 0.01);
+        assert_eq!(merged.len(), 1);
+        assert!(merged[0].closed);
+        assert_eq!(merged[0].points.len(), 3);
+    }
+
+    #[test]
+    fn dedup_fuses_overlaps() {
+        let paths = vec![
+            line(pt2(0., 0.), pt2(2., 0.)),
+            line(pt2(3., 0.), pt2(1., 0.)),
+            line(pt2(2., 0.), pt2(0., 0.)),
+            line(pt2(5., 0.), pt2(6., 0.)),
+            line(pt2(0., 1.), pt2(2., 1.)),
+        ];
+        let deduped = dedup(paths, 0.01);
+        assert_eq!(deduped.len(), 3);
+        let long = deduped.iter().find(|p| p.start().distance(p.end()) > 2.5);
+        assert!(long.is_some());
+    }
+
+    #[test]
+    fn dedup_bundles_across_horizontal() {
+        let paths = vec![
+            line(pt2(0., 0.), pt2(4., 0.0008)),
+            line(pt2(1., 0.0008), pt2(5., 0.)),
+        ];
+        assert_eq!(dedup(paths, 0.01).len(), 1);
+    }
+
+    #[test]
+    fn report_counts_paths_and_travel() {
+        let paths = vec![
+            line(pt2(10., 0.), pt2(11., 0.)),
+            line(pt2(0., 0.), pt2(1., 0.)),
+            line(pt2(1., 0.), pt2(0., 0.)),
+        ];
+        let (paths, report) = optimize(paths, &Options::default());
+        assert_eq!(report.paths_before, 3);
+        assert_eq!(report.paths_after, paths.len());
+        assert!(report.travel_after < report.travel_before);
+    }
+}
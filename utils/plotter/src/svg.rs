@@ -0,0 +1,262 @@
+//! SVG output laid out on a physical page, one Inkscape layer per color.
+use crate::{optimize, Path};
+use nannou::prelude::*;
+use std::fmt::Write as _;
+use std::fs;
+use std::io;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Unit {
+    Mm,
+    Cm,
+    In,
+    Px,
+}
+
+impl Unit {
+    fn suffix(self) -> &'static str {
+        match self {
+            Unit::Mm => "mm",
+            Unit::Cm => "cm",
+            Unit::In => "in",
+            Unit::Px => "px",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Page {
+    pub width: f32,
+    pub height: f32,
+    pub unit: Unit,
+}
+
+impl Page {
+    pub fn new(width: f32, height: f32, unit: Unit) -> Self {
+        Page {
+            width,
+            height,
+            unit,
+        }
+    }
+
+    pub fn a4() -> Self {
+        Page::new(210., 297., Unit::Mm)
+    }
+
+    pub fn a3() -> Self {
+        Page::new(297., 420., Unit::Mm)
+    }
+
+    pub fn letter() -> Self {
+        Page::new(8.5, 11., Unit::In)
+    }
+
+    pub fn landscape(self) -> Self {
+        Page::new(
+            self.width.max(self.height),
+            self.width.min(self.height),
+            self.unit,
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SvgOptions {
+    pub page: Page,
+    /// Empty border around the drawing, in page units.
+    pub margin: f32,
+    /// Stroke width written to the file, in page units.
+    pub pen_width: f32,
+    /// Join paths whose ends are closer than this, in sketch units.
+    pub merge: Option<f32>,
+    /// Reorder paths to cut down pen-up travel.
+    pub optimize: bool,
+}
+
+impl Default for SvgOptions {
+    fn default() -> Self {
+        SvgOptions {
+            page: Page::a4(),
+            margin: 10.,
+            pen_width: 0.3,
+            merge: None,
+            optimize: false,
+        }
+    }
+}
+
+/// Paths grouped by color, in order of first appearance.
+pub fn layers(paths: &[Path]) -> Vec<(String, Vec<Path>)> {
+    let mut layers: Vec<(String, Vec<Path>)> = Vec::new();
+    for path in paths {
+        let name = path.layer();
+        match layers.iter_mut().find(|(n, _)| *n == name) {
+            Some((_, group)) => group.push(path.clone()),
+            None => layers.push((name, vec![path.clone()])),
+        }
+    }
+    layers
+}
+
+/// Render `paths` to an SVG document, `area` is scaled to fit the page.
+pub fn to_svg(paths: &[Path], area: Rect, options: &SvgOptions) -> String {
+    let page = options.page;
+    let scale = ((page.width - 2. * options.margin) / area.w())
+        .min((page.height - 2. * options.margin) / area.h());
+    let to_page = |p: Point2| {
+        (
+            page.width / 2. + (p.x - area.x()) * scale,
+            page.height / 2. - (p.y - area.y()) * scale,
+        )
+    };
+
+    let mut out = String::new();
+    let unit = page.unit.suffix();
+    writeln!(
+        out,
+        r#"<?xml version="1.0" encoding="UTF-8" standalone="no"?>"#
+    )
+    .unwrap();
+    writeln!(
+        out,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" xmlns:inkscape="http://www.inkscape.org/namespaces/inkscape" width="{w}{u}" height="{h}{u}" viewBox="0 0 {w} {h}">"#,
+        w = page.width,
+        h = page.height,
+        u = unit
+    )
+    .unwrap();
+
+    for (i, (color, mut group)) in layers(paths).into_iter().enumerate() {
+        if let Some(tolerance) = options.merge {
+            group = optimize::merge(group, tolerance);
+        }
+        if options.optimize {
+            group = optimize::order_greedy(group);
+        }
+        writeln!(
+            out,
+            r#"  <g id="layer{n}" inkscape:groupmode="layer" inkscape:label="{n} {c}" fill="none" stroke="{c}" stroke-width="{pw}" stroke-linecap="round" stroke-linejoin="round">"#,
+            n = i + 1,
+            c = color,
+            pw = options.pen_width
+        )
+        .unwrap();
+        for path in &group {
+            out.push_str("    <path d=\"");
+            for (j, &p) in path.points.iter().enumerate() {
+                let (x, y) = to_page(p);
+                let cmd = if j == 0 { "M" } else { " L" };
+                write!(out, "{}{:.3} {:.3}", cmd, x, y).unwrap();
+            }
+            if path.closed {
+                out.push_str(" Z");
+            }
+            out.push_str("\"/>\n");
+        }
+        out.push_str("  </g>\n");
+    }
+    out.push_str("</svg>\n");
+
+    out
+}
+
+pub fn save<P: AsRef<std::path::Path>>(
+    file: P,
+    paths: &[Path],
+    area: Rect,
+    options: &SvgOptions,
+) -> io::Result<()> {
+    fs::write(file, to_svg(paths, area, options))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Value of `name="..."` in the first tag of `xml` that has it.
+    fn attribute<'a>(xml: &'a str, name: &str) -> &'a str {
+        let start = xml.find(&format!(" {}=\"", name)).unwrap() + name.len() + 3;
+        &xml[start..start + xml[start..].find('"').unwrap()]
+    }
+
+    fn tags<'a>(xml: &'a str, tag: &str) -> Vec<&'a str> {
+        xml.match_indices(&format!("<{} ", tag))
+            .map(|(i, _)| &xml[i..i + xml[i..].find('>').unwrap()])
+            .collect()
+    }
+
+    fn numbers(d: &str) -> Vec<f32> {
+        d.split(|c: char| c.is_whitespace() || c.is_alphabetic())
+            .filter(|s| !s.is_empty())
+            .map(|s| s.parse().unwrap())
+            .collect()
+    }
+
+    fn options() -> SvgOptions {
+        SvgOptions {
+            page: Page::new(120., 100., Unit::Mm),
+            margin: 10.,
+            ..SvgOptions::default()
+        }
+    }
+
+    #[test]
+    fn page_sets_size_and_view_box() {
+        let svg = to_svg(&[], Rect::from_w_h(10., 10.), &options());
+        let root = tags(&svg, "svg")[0];
+        assert_eq!(attribute(root, "width"), "120mm");
+        assert_eq!(attribute(root, "height"), "100mm");
+        assert_eq!(attribute(root, "viewBox"), "0 0 120 100");
+        assert!(tags(&svg, "g").is_empty());
+    }
+
+    #[test]
+    fn one_layer_per_color() {
+        let red = srgba(1., 0., 0., 1.);
+        let blue = srgba(0., 0., 1., 1.);
+        let line = |y: f32, color| Path::open(vec![pt2(-1., y), pt2(1., y)], color, 1.);
+        let paths = [line(0., red), line(1., blue), line(2., red)];
+        let svg = to_svg(&paths, Rect::from_w_h(10., 10.), &options());
+        let groups: Vec<&str> = svg.split("<g ").skip(1).collect();
+        assert_eq!(groups.len(), 2);
+        assert_eq!(attribute(groups[0], "stroke"), paths[0].layer());
+        assert_eq!(attribute(groups[0], "inkscape:groupmode"), "layer");
+        assert_eq!(tags(groups[0], "path").len(), 2);
+        assert_eq!(attribute(groups[1], "stroke"), paths[1].layer());
+        assert_eq!(tags(groups[1], "path").len(), 1);
+    }
+
+    #[test]
+    fn merge_joins_paths_in_a_layer() {
+        let black = srgba(0., 0., 0., 1.);
+        let paths = [
+            Path::open(vec![pt2(0., 0.), pt2(1., 0.)], black, 1.),
+            Path::open(vec![pt2(1., 0.), pt2(1., 1.)], black, 1.),
+        ];
+        let area = Rect::from_w_h(10., 10.);
+        assert_eq!(tags(&to_svg(&paths, area, &options()), "path").len(), 2);
+        let merged = SvgOptions {
+            merge: Some(0.01),
+            ..options()
+        };
+        assert_eq!(tags(&to_svg(&paths, area, &merged), "path").len(), 1);
+    }
+
+    #[test]
+    fn sketch_is_scaled_to_page_and_flipped() {
+        // 40x20 sketch units into the 100x80 mm left inside the margin,
+        // the width limits to 2.5 mm per unit.
+        let area = Rect::from_x_y_w_h(5., 0., 40., 20.);
+        let path = Path::closed(
+            vec![pt2(5., 0.), pt2(5., 10.), pt2(-15., -10.)],
+            srgba(0., 0., 0., 1.),
+            1.,
+        );
+        let svg = to_svg(&[path], area, &options());
+        let d = attribute(tags(&svg, "path")[0], "d");
+        assert!(d.starts_with('M') && d.ends_with(" Z"));
+        // Up in the sketch is down the page.
+        assert_eq!(numbers(d), vec![60., 50., 60., 25., 10., 75.]);
+    }
+}
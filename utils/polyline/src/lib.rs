@@ -0,0 +1,394 @@
+use nannou::prelude::*;
+
+/// Corner style used by `offset` on the outer side of a bend.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Join {
+    /// Sharp corner, falls back to a bevel once the miter is longer
+    /// than `limit` times the offset distance.
+    Miter(f32),
+    /// Arc around the original vertex.
+    Round,
+    /// Straight cut between the two offset segments.
+    Bevel,
+}
+
+/// Total arc length of the path.
+pub fn length(points: &[Point2]) -> f32 {
+    points.windows(2).map(|s| s[0].distance(s[1])).sum()
+}
+
+/// Running arc length at every vertex, starting with 0.
+pub fn cumulative_length(points: &[Point2]) -> Vec<f32> {
+    let mut acc = 0.;
+    let mut res = Vec::with_capacity(points.len());
+    for (i, p) in points.iter().enumerate() {
+        if i > 0 {
+            acc += points[i - 1].distance(*p);
+        }
+        res.push(acc);
+    }
+    res
+}
+
+/// Point and unit tangent at `distance` along the path.
+///
+/// The distance is clamped to the path, so negative values give the first
+/// point and anything past the end gives the last one.
+pub fn sample(points: &[Point2], distance: f32) -> Option<(Point2, Vector2)> {
+    match points.len() {
+        0 => None,
+        1 => Some((points[0], vec2(0., 0.))),
+        _ => {
+            let (i, t) = locate(points, distance);
+            let p = points[i].lerp(points[i + 1], t);
+            Some((p, tangent(points, i)))
+        }
+    }
+}
+
+/// Resample the path with points evenly spaced by arc length.
+///
+/// Both endpoints are kept, so the last step may be shorter than `spacing`.
+pub fn resample(points: &[Point2], spacing: f32) -> Vec<Point2> {
+    let total = length(points);
+    if points.len() < 2 || spacing <= 0. || total == 0. {
+        return points.to_vec();
+    }
+    let steps = (total / spacing).floor() as usize;
+    let mut distances: Vec<f32> = (0..=steps).map(|i| i as f32 * spacing).collect();
+    if total - steps as f32 * spacing > spacing * 1e-3 {
+        distances.push(total);
+    }
+    points_at(points, &distances)
+}
+
+/// Resample the path into exactly `count` points evenly spaced by arc length.
+pub fn resample_count(points: &[Point2], count: usize) -> Vec<Point2> {
+    if points.len() < 2 || count < 2 {
+        return points.iter().take(count).cloned().collect();
+    }
+    let total = length(points);
+    let distances: Vec<f32> = (0..count)
+        .map(|i| total * i as f32 / (count - 1) as f32)
+        .collect();
+    points_at(points, &distances)
+}
+
+/// Ramer–Douglas–Peucker simplification.
+///
+/// Drops every vertex that lies closer than `epsilon` to the simplified path.
+pub fn simplify(points: &[Point2], epsilon: f32) -> Vec<Point2> {
+    if points.len() < 3 {
+        return points.to_vec();
+    }
+    let mut keep = vec![false; points.len()];
+    keep[0] = true;
+    keep[points.len() - 1] = true;
+
+    let mut stack = vec![(0, points.len() - 1)];
+    while let Some((first, last)) = stack.pop() {
+        let mut max_dist = 0.;
+        let mut index = first;
+        for i in first + 1..last {
+            let d = segment_distance(points[i], points[first], points[last]);
+            if d > max_dist {
+                max_dist = d;
+                index = i;
+            }
+        }
+        if max_dist > epsilon {
+            keep[index] = true;
+            stack.push((first, index));
+            stack.push((index, last));
+        }
+    }
+
+    points
+        .iter()
+        .zip(keep)
+        .filter_map(|(&p, k)| if k { Some(p) } else { None })
+        .collect()
+}
+
+/// Parallel curve at `distance` from the path.
+///
+/// Positive distances offset to the left of the direction of travel,
+/// negative ones to the right. `join` shapes the outer side of each bend,
+/// inner corners are always cut at the intersection of the offset segments.
+pub fn offset(points: &[Point2], distance: f32, join: Join) -> Vec<Point2> {
+    let points = dedup(points);
+    if points.len() < 2 || distance == 0. {
+        return points;
+    }
+
+    let dirs: Vec<Vector2> = points
+        .windows(2)
+        .map(|s| (s[1] - s[0]).normalize())
+        .collect();
+    let normals: Vec<Vector2> = dirs.iter().map(|d| vec2(-d.y, d.x) * distance).collect();
+
+    let mut res = vec![points[0] + normals[0]];
+    for j in 1..points.len() - 1 {
+        let p = points[j];
+        let (n0, n1) = (normals[j - 1], normals[j]);
+        let (a, b) = (p + n0, p + n1);
+        let turn = cross(dirs[j - 1], dirs[j]);
+        if turn.abs() < 1e-6 && dirs[j - 1].dot(dirs[j]) > 0. {
+            res.push(a);
+            continue;
+        }
+
+        let inner = turn * distance > 0.;
+        let miter = miter_point(p, n0, n1, distance);
+        match (inner, join) {
+            (true, _) => match miter {
+                Some(m) => res.push(m),
+                None => {
+                    res.push(a);
+                    res.push(b);
+                }
+            },
+            (false, Join::Miter(limit)) => match miter {
+                Some(m) if m.distance(p) <= limit * distance.abs() => res.push(m),
+                _ => {
+                    res.push(a);
+                    res.push(b);
+                }
+            },
+            (false, Join::Bevel) => {
+                res.push(a);
+                res.push(b);
+            }
+            (false, Join::Round) => {
+                res.extend(arc(p, n0, n1, turn));
+            }
+        }
+    }
+    res.push(points[points.len() - 1] + normals[normals.len() - 1]);
+
+    res
+}
+
+/// Split the path in two at `distance` along it.
+///
+/// The split point belongs to both halves.
+pub fn split(points: &[Point2], distance: f32) -> (Vec<Point2>, Vec<Point2>) {
+    let total = length(points);
+    (trim(points, 0., distance), trim(points, distance, total))
+}
+
+/// Part of the path between arc lengths `start` and `end`.
+pub fn trim(points: &[Point2], start: f32, end: f32) -> Vec<Point2> {
+    if points.len() < 2 {
+        return points.to_vec();
+    }
+    let total = length(points);
+    let start = start.max(0.).min(total);
+    let end = end.max(start).min(total);
+
+    let cumulative = cumulative_length(points);
+    let mut res = vec![point_at(points, start)];
+    res.extend(
+        points
+            .iter()
+            .zip(cumulative)
+            .filter(|&(_, d)| d > start && d < end)
+            .map(|(&p, _)| p),
+    );
+    res.push(point_at(points, end));
+
+    res
+}
+
+fn point_at(points: &[Point2], distance: f32) -> Point2 {
+    let (i, t) = locate(points, distance);
+    points[i].lerp(points[i + 1], t)
+}
+
+/// Points at the given ascending arc lengths, found in a single pass.
+fn points_at(points: &[Point2], distances: &[f32]) -> Vec<Point2> {
+    let mut res = Vec::with_capacity(distances.len());
+    let mut i = 0;
+    let mut walked = 0.;
+    let mut seg = points[0].distance(points[1]);
+    for &d in distances {
+        while d > walked + seg && i < points.len() - 2 {
+            walked += seg;
+            i += 1;
+            seg = points[i].distance(points[i + 1]);
+        }
+        let t = if seg > 0. {
+            ((d - walked) / seg).clamp(0., 1.)
+        } else {
+            0.
+        };
+        res.push(points[i].lerp(points[i + 1], t));
+    }
+    res
+}
+
+/// Segment index and interpolation factor at `distance` along the path.
+fn locate(points: &[Point2], distance: f32) -> (usize, f32) {
+    let mut walked = 0.;
+    for i in 0..points.len() - 1 {
+        let seg = points[i].distance(points[i + 1]);
+        if distance <= walked + seg || i == points.len() - 2 {
+            let t = if seg > 0. {
+                ((distance - walked) / seg).clamp(0., 1.)
+            } else {
+                0.
+            };
+            return (i, t);
+        }
+        walked += seg;
+    }
+    unreachable!()
+}
+
+/// Direction of segment `i`, looking at neighbours when it is degenerate.
+fn tangent(points: &[Point2], i: usize) -> Vector2 {
+    let forward = points[i + 1..].iter().find(|&&p| p != points[i]);
+    let backward = points[..=i].iter().rev().find(|&&p| p != points[i + 1]);
+    match (forward, backward) {
+        (Some(&p), _) => (p - points[i]).normalize(),
+        (None, Some(&p)) => (points[i + 1] - p).normalize(),
+        _ => vec2(0., 0.),
+    }
+}
+
+fn dedup(points: &[Point2]) -> Vec<Point2> {
+    let mut res: Vec<Point2> = Vec::with_capacity(points.len());
+    for &p in points {
+        if res.last().map(|&last| last.distance2(p) > 1e-12).unwrap_or(true) {
+            res.push(p);
+        }
+    }
+    res
+}
+
+fn cross(a: Vector2, b: Vector2) -> f32 {
+    a.x * b.y - a.y * b.x
+}
+
+fn segment_distance(p: Point2, a: Point2, b: Point2) -> f32 {
+    let ab = b - a;
+    let len2 = ab.magnitude2();
+    if len2 == 0. {
+        return p.distance(a);
+    }
+    let t = ((p - a).dot(ab) / len2).clamp(0., 1.);
+    p.distance(a + ab * t)
+}
+
+/// Intersection of the two offset segments meeting at `p`.
+fn miter_point(p: Point2, n0: Vector2, n1: Vector2, distance: f32) -> Option<Point2> {
+    let bisector = n0 + n1;
+    if bisector.magnitude2() < 1e-12 {
+        return None;
+    }
+    let bisector = bisector.normalize();
+    let cos_half = bisector.dot(n1) / distance.abs();
+    if cos_half.abs() < 1e-6 {
+        return None;
+    }
+    Some(p + bisector * (distance.abs() / cos_half))
+}
+
+/// Arc around `p` from `p + n0` to `p + n1` turning the outer way.
+fn arc(p: Point2, n0: Vector2, n1: Vector2, turn: f32) -> Vec<Point2> {
+    let start = n0.angle();
+    let mut sweep = n1.angle() - start;
+    if turn > 0. && sweep < 0. {
+        sweep += TAU;
+    } else if turn < 0. && sweep > 0. {
+        sweep -= TAU;
+    }
+    let radius = n0.magnitude();
+    let steps = ((sweep.abs() * radius.sqrt()).ceil() as usize).max(2);
+    (0..=steps)
+        .map(|i| {
+            let a = start + sweep * i as f32 / steps as f32;
+            p + vec2(a.cos(), a.sin()) * radius
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn approx(a: Point2, b: Point2) -> bool {
+        a.distance(b) < 1e-4
+    }
+
+    #[test]
+    fn length_and_sample() {
+        let path = vec![pt2(0., 0.), pt2(10., 0.), pt2(10., 10.)];
+        assert_eq!(length(&path), 20.);
+        let (p, t) = sample(&path, 15.).unwrap();
+        assert!(approx(p, pt2(10., 5.)));
+        assert!(approx(t, vec2(0., 1.)));
+        let (p, _) = sample(&path, 100.).unwrap();
+        assert!(approx(p, pt2(10., 10.)));
+    }
+
+    #[test]
+    fn resample_keeps_endpoints() {
+        let path = vec![pt2(0., 0.), pt2(10., 0.), pt2(10., 5.)];
+        let res = resample(&path, 4.);
+        assert_eq!(res.len(), 5);
+        assert!(approx(res[1], pt2(4., 0.)));
+        assert!(approx(res[3], pt2(10., 2.)));
+        assert!(approx(*res.last().unwrap(), pt2(10., 5.)));
+
+        let res = resample_count(&path, 4);
+        assert_eq!(res.len(), 4);
+        assert!(approx(res[2], pt2(10., 0.)));
+    }
+
+    #[test]
+    fn simplify_drops_collinear() {
+        let path = vec![
+            pt2(0., 0.),
+            pt2(1., 0.01),
+            pt2(2., 0.),
+            pt2(3., 5.),
+            pt2(4., 0.),
+        ];
+        let res = simplify(&path, 0.1);
+        assert_eq!(res, vec![pt2(0., 0.), pt2(2., 0.), pt2(3., 5.), pt2(4., 0.)]);
+    }
+
+    #[test]
+    fn offset_joins() {
+        let path = vec![pt2(0., 0.), pt2(10., 0.), pt2(10., 10.)];
+        // turning left, so the right side is the outer one
+        let miter = offset(&path, -1., Join::Miter(4.));
+        assert_eq!(miter.len(), 3);
+        assert!(approx(miter[1], pt2(11., -1.)));
+        let bevel = offset(&path, -1., Join::Bevel);
+        assert_eq!(bevel.len(), 4);
+        assert!(approx(bevel[1], pt2(10., -1.)));
+        assert!(approx(bevel[2], pt2(11., 0.)));
+        let round = offset(&path, -1., Join::Round);
+        assert!(round.len() > 4);
+        let join = &round[1..round.len() - 1];
+        assert!(join
+            .iter()
+            .all(|p| (p.distance(pt2(10., 0.)) - 1.).abs() < 1e-4));
+        // around the outside of the corner, not through the stroke
+        assert!(join.iter().all(|p| p.x >= 10. - 1e-4 && p.y <= 1e-4));
+        let inner = offset(&path, 1., Join::Round);
+        assert!(approx(inner[1], pt2(9., 1.)));
+    }
+
+    #[test]
+    fn split_and_trim() {
+        let path = vec![pt2(0., 0.), pt2(10., 0.), pt2(10., 10.)];
+        let (a, b) = split(&path, 5.);
+        assert_eq!(a, vec![pt2(0., 0.), pt2(5., 0.)]);
+        assert_eq!(b, vec![pt2(5., 0.), pt2(10., 0.), pt2(10., 10.)]);
+        let t = trim(&path, 5., 15.);
+        assert_eq!(t, vec![pt2(5., 0.), pt2(10., 0.), pt2(10., 5.)]);
+    }
+}
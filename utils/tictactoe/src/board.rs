@@ -0,0 +1,173 @@
+use crate::brain;
+use crate::grid::Grid;
+use crate::notation::Game;
+use crate::player::Player;
+use crate::strategy::Strategy;
+use crate::Field;
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum BoardState {
+    /// The winner and the first and last cell of their line.
+    Winner(Player, (usize, usize)),
+    Tie,
+    InGame,
+}
+
+/// Who is playing, `Waiting` on the menu before a game.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameMode {
+    SinglePlayer,
+    MultiPlayer,
+    /// Against a `net::Remote`, one mark on either side.
+    Networked,
+    Waiting,
+}
+
+/// A game in progress, rules only, front ends draw it and turn their input
+/// into `play`.
+#[derive(Debug, Clone)]
+pub struct Board {
+    pub grid: Grid,
+    pub board: Vec<Field>,
+    pub player_1: Player,
+    pub player_2: Player,
+    pub current_player: Player,
+    pub state: BoardState,
+    /// Cells played so far, X first.
+    pub history: Vec<usize>,
+    /// Moves taken back, the last one is redone first.
+    undone: Vec<usize>,
+}
+
+impl Board {
+    pub fn new(grid: Grid) -> Self {
+        Board {
+            grid,
+            board: (0..grid.len()).map(|_| Field::Empty).collect(),
+            player_1: Player::Human,
+            player_2: Player::Computer,
+            current_player: Player::Human,
+            state: BoardState::InGame,
+            history: Vec::new(),
+            undone: Vec::new(),
+        }
+    }
+
+    /// The board after the moves of `game`, which has to be a game on it.
+    pub fn from_game(game: &Game) -> Self {
+        let mut board = Self::new(game.grid);
+        for &cell in &game.moves {
+            board.play(cell);
+        }
+        board
+    }
+
+    pub fn game(&self) -> Game {
+        Game {
+            grid: self.grid,
+            moves: self.history.clone(),
+        }
+    }
+
+    /// The same game after its first `moves` moves.
+    pub fn replay(&self, moves: usize) -> Self {
+        let mut game = self.game();
+        game.moves.truncate(moves);
+        Self::from_game(&game)
+    }
+
+    /// Cells the current player may mark, none once the game is over.
+    pub fn legal_moves(&self) -> Vec<usize> {
+        if self.state != BoardState::InGame {
+            return Vec::new();
+        }
+        (0..self.board.len())
+            .filter(|&cell| self.board[cell] == Field::Empty)
+            .collect()
+    }
+
+    pub fn is_legal(&self, cell: usize) -> bool {
+        self.state == BoardState::InGame && self.board.get(cell) == Some(&Field::Empty)
+    }
+
+    /// Marks `cell` for the current player, `false` and nothing changes if
+    /// that isn't a legal move.
+    pub fn play(&mut self, cell: usize) -> bool {
+        if !self.is_legal(cell) {
+            return false;
+        }
+        self.board[cell] = Field::from(self.current_player);
+        self.history.push(cell);
+        self.undone.clear();
+        self.made_move();
+        true
+    }
+
+    /// Takes back the last move, `false` if there is none.
+    pub fn undo(&mut self) -> bool {
+        match self.history.pop() {
+            Some(cell) => {
+                self.board[cell] = Field::Empty;
+                self.undone.push(cell);
+                self.made_move();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Plays the last move taken back again, `false` if there is none.
+    pub fn redo(&mut self) -> bool {
+        match self.undone.pop() {
+            Some(cell) => {
+                self.board[cell] = Field::from(self.current_player);
+                self.history.push(cell);
+                self.made_move();
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn computer_move(&mut self, strategy: &mut dyn Strategy) {
+        if self.state == BoardState::InGame && self.current_player == Player::Computer {
+            let position = strategy.play(&self.grid, &self.board, self.current_player);
+            self.play(position);
+        }
+    }
+
+    pub fn made_move(&mut self) {
+        self.state = brain::check_winner(&self.grid, &self.board);
+        self.current_player = -self.current_player;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn undo_and_redo() {
+        let game = Game::parse("3x3\nb2 a1 c3").unwrap();
+        let mut board = Board::from_game(&game);
+        assert_eq!(board.current_player, Player::Computer);
+        assert!(board.undo() && board.undo());
+        assert_eq!(board.history, vec![4]);
+        assert_eq!(board.board[0], Field::Empty);
+        assert!(board.redo());
+        assert_eq!(board.board[0], Field::O);
+        assert_eq!(board.replay(1).history, vec![4]);
+
+        assert!(!board.play(0), "a1 is taken");
+        assert!(board.play(2));
+        assert!(!board.redo(), "a new move drops what was undone");
+        board.play(8);
+        board.play(6);
+        assert_eq!(board.state, BoardState::Winner(Player::Human, (2, 6)));
+        assert!(!board.play(1) && board.legal_moves().is_empty());
+        assert!(board.undo());
+        assert_eq!(board.state, BoardState::InGame);
+        assert_eq!(board.legal_moves(), vec![1, 3, 5, 6, 7]);
+        assert_eq!(board.game().to_string(), "3x3 k3\n1. b2 a1\n2. c1 c3\n");
+    }
+}
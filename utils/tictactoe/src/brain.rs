@@ -0,0 +1,850 @@
+use crate::board::BoardState;
+use crate::eval::Eval;
+use crate::field::Field;
+use crate::grid::{Grid, Lines};
+use crate::player::Player;
+use std::collections::HashMap;
+
+/// Wins are worth more than any evaluation of an unfinished position.
+const WIN: i64 = 1 << 40;
+const INFINITY: i64 = WIN << 2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Bound {
+    Exact,
+    Lower,
+    Upper,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Entry {
+    value: i64,
+    bound: Bound,
+    /// Plies searched below the position, wins and ties are always exact.
+    depth: usize,
+    /// Best cell, in the coordinates of the canonical position.
+    best: usize,
+}
+
+/// Negamax with alpha-beta pruning over a transposition table.
+///
+/// Positions are stored once for all rotations and mirrors of the board,
+/// keyed on the smallest of their Zobrist hashes. Win scores only depend on
+/// the position, one more than the empty cells left after the winning move,
+/// so they stay valid whatever move order led there.
+///
+/// Small boards are searched to the end. On larger ones the search stops
+/// after `depth` plies and scores open lines instead, and beyond 4×4 it only
+/// tries cells next to a mark.
+#[derive(Debug)]
+pub struct Search {
+    grid: Grid,
+    depth: Option<usize>,
+    lines: Lines,
+    symmetries: Vec<Vec<usize>>,
+    /// `inverse[s][cell]` is where `cell` lands under symmetry `s`.
+    inverse: Vec<Vec<usize>>,
+    /// Random keys of every cell, for an X and for an O.
+    zobrist: Vec<[u64; 2]>,
+    table: HashMap<(u64, bool), Entry>,
+    /// Positions visited so far, table hits included.
+    pub nodes: usize,
+}
+
+impl Search {
+    pub fn new(grid: Grid) -> Self {
+        let lines = Lines::new(&grid);
+        let symmetries = grid.symmetries();
+        let inverse = symmetries
+            .iter()
+            .map(|symmetry| {
+                let mut inverse = vec![0; symmetry.len()];
+                for (i, &cell) in symmetry.iter().enumerate() {
+                    inverse[cell] = i;
+                }
+                inverse
+            })
+            .collect();
+        // splitmix64, the keys only have to be well spread.
+        let mut state = 0x9e37_79b9_7f4a_7c15u64;
+        let mut random = || {
+            state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+            let mut z = state;
+            z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+            z ^ (z >> 31)
+        };
+        let zobrist = (0..grid.len()).map(|_| [random(), random()]).collect();
+        let depth = match grid.len() {
+            0..=9 => None,
+            10..=16 => Some(6),
+            _ => Some(4),
+        };
+        Search {
+            grid,
+            depth,
+            lines,
+            symmetries,
+            inverse,
+            zobrist,
+            table: HashMap::new(),
+            nodes: 0,
+        }
+    }
+
+    /// Plies to look ahead, `None` searches to the end of the game.
+    pub fn depth(mut self, depth: Option<usize>) -> Self {
+        self.depth = depth;
+        self
+    }
+
+    /// Best move for `player`. Wins score `cells + 1` minus the plies to
+    /// reach them, which is what `minimax` gives on 3×3, positive when the
+    /// computer wins.
+    pub fn best_move(&mut self, board: &[Field], player: Player) -> Eval {
+        let win = self.grid.len() as i64 + 1;
+        match check_winner(&self.grid, board) {
+            BoardState::InGame => {}
+            BoardState::Winner(Player::Computer, _) => return Eval::new(0, win),
+            BoardState::Winner(Player::Human, _) => return Eval::new(0, -win),
+            BoardState::Tie => return Eval::new(0, 0),
+        }
+        let mut cells = board.to_vec();
+        let mut keys = vec![0; self.symmetries.len()];
+        for (cell, &field) in board.iter().enumerate() {
+            if field != Field::Empty {
+                self.toggle(&mut keys, cell, field);
+            }
+        }
+        let empty = board.iter().filter(|&&f| f == Field::Empty).count();
+        let depth = self.depth.unwrap_or(empty);
+        let (value, position) = self.negamax(
+            &mut cells, &mut keys, player, empty, depth, -INFINITY, INFINITY,
+        );
+        let score = if value.abs() > WIN {
+            value.signum() * (value.abs() - WIN + (self.grid.len() - empty) as i64)
+        } else {
+            value
+        };
+        match player {
+            Player::Computer => Eval::new(position, score),
+            Player::Human => Eval::new(position, -score),
+        }
+    }
+
+    /// Every move for `player` with the score of the position it leads to,
+    /// in the same terms as `best_move`.
+    pub fn move_scores(&mut self, board: &[Field], player: Player) -> Vec<Eval> {
+        let mut cells = board.to_vec();
+        (0..board.len())
+            .filter(|&cell| board[cell] == Field::Empty)
+            .map(|cell| {
+                cells[cell] = Field::from(player);
+                let score = self.best_move(&cells, -player).score;
+                cells[cell] = Field::Empty;
+                Eval::new(cell, score)
+            })
+            .collect()
+    }
+
+    /// Value of `board` for `player`, who is about to move, and the cell
+    /// that gets it. Nobody has won yet.
+    #[allow(clippy::too_many_arguments)]
+    fn negamax(
+        &mut self,
+        board: &mut [Field],
+        keys: &mut [u64],
+        player: Player,
+        empty: usize,
+        depth: usize,
+        mut alpha: i64,
+        beta: i64,
+    ) -> (i64, usize) {
+        self.nodes += 1;
+        if empty == 0 {
+            return (0, 0);
+        }
+        if depth == 0 {
+            return (self.evaluate(board, player), 0);
+        }
+
+        let (key, symmetry) = keys
+            .iter()
+            .enumerate()
+            .map(|(symmetry, &key)| (key, symmetry))
+            .min()
+            .unwrap();
+        let key = (key, player == Player::Computer);
+        let mut first = None;
+        if let Some(entry) = self.table.get(&key) {
+            let best = self.symmetries[symmetry][entry.best];
+            let value = entry.value;
+            match entry.bound {
+                _ if entry.depth < depth => first = Some(best),
+                Bound::Exact => return (value, best),
+                Bound::Lower if value >= beta => return (value, best),
+                Bound::Upper if value <= alpha => return (value, best),
+                _ => first = Some(best),
+            }
+        }
+
+        let field = Field::from(player);
+        let start = alpha;
+        let moves = self.moves(board, player, first);
+        let (mut value, mut best) = (-INFINITY, moves[0]);
+        for cell in moves {
+            board[cell] = field;
+            self.toggle(keys, cell, field);
+            let score = if self.lines.completes(board, cell) {
+                WIN + empty as i64
+            } else {
+                -self
+                    .negamax(board, keys, -player, empty - 1, depth - 1, -beta, -alpha)
+                    .0
+            };
+            self.toggle(keys, cell, field);
+            board[cell] = Field::Empty;
+            if score > value {
+                value = score;
+                best = cell;
+            }
+            alpha = alpha.max(value);
+            if alpha >= beta {
+                break;
+            }
+        }
+
+        let bound = if value <= start {
+            Bound::Upper
+        } else if value >= beta {
+            Bound::Lower
+        } else {
+            Bound::Exact
+        };
+        let entry = Entry {
+            value,
+            bound,
+            depth,
+            best: self.inverse[symmetry][best],
+        };
+        self.table.insert(key, entry);
+        (value, best)
+    }
+
+    /// Put `field` on `cell` in every symmetric hash, or take it off again.
+    fn toggle(&self, keys: &mut [u64], cell: usize, field: Field) {
+        let side = (field == Field::X) as usize;
+        for (key, inverse) in keys.iter_mut().zip(&self.inverse) {
+            *key ^= self.zobrist[inverse[cell]][side];
+        }
+    }
+
+    /// Marks of `player` and of the other player on a line.
+    fn count(&self, board: &[Field], line: usize, player: Player) -> (u32, u32) {
+        let (own, other) = (Field::from(player), Field::from(-player));
+        self.lines.runs[line]
+            .iter()
+            .fold((0, 0), |(mine, theirs), &cell| match board[cell] {
+                f if f == own => (mine + 1, theirs),
+                f if f == other => (mine, theirs + 1),
+                _ => (mine, theirs),
+            })
+    }
+
+    /// Open lines of `player` minus those of the other player, each worth
+    /// ten times more for every mark on it.
+    fn evaluate(&self, board: &[Field], player: Player) -> i64 {
+        (0..self.lines.runs.len())
+            .map(|line| match self.count(board, line, player) {
+                (0, 0) => 0,
+                (mine, 0) => 10i64.pow(mine - 1),
+                (0, theirs) => -(10i64.pow(theirs - 1)),
+                _ => 0,
+            })
+            .sum()
+    }
+
+    /// Empty cells to try, the table's move first, then the ones that open
+    /// or block the most lines. Beyond 4×4 only cells next to a mark are
+    /// tried.
+    fn moves(&self, board: &[Field], player: Player, first: Option<usize>) -> Vec<usize> {
+        let near = |cell: usize| {
+            let (x, y) = self.grid.position(cell);
+            let (x, y) = (x as isize, y as isize);
+            (-1..=1).any(|dy| {
+                (-1..=1).any(|dx| {
+                    let (x, y) = (x + dx, y + dy);
+                    x >= 0
+                        && y >= 0
+                        && (x as usize) < self.grid.columns
+                        && (y as usize) < self.grid.rows
+                        && board[self.grid.cell(x as usize, y as usize)] != Field::Empty
+                })
+            })
+        };
+        let mut moves: Vec<usize> = (0..board.len())
+            .filter(|&cell| board[cell] == Field::Empty)
+            .collect();
+        if self.grid.len() > 16 && moves.len() < board.len() {
+            moves.retain(|&cell| near(cell));
+        }
+        moves.sort_by_cached_key(|&cell| {
+            let urgency: i64 = self.lines.through[cell]
+                .iter()
+                .map(|&line| match self.count(board, line, player) {
+                    (mine, 0) => 10i64.pow(mine),
+                    (0, theirs) => 10i64.pow(theirs),
+                    _ => 0,
+                })
+                .sum();
+            (Some(cell) != first, -urgency)
+        });
+        moves
+    }
+}
+
+/// Best move for `player` from a fresh `Search`.
+pub fn negamax(grid: &Grid, board: &[Field], player: Player) -> Eval {
+    Search::new(*grid).best_move(board, player)
+}
+
+/// Winner and the first and last cell of the line they completed.
+pub fn check_winner(grid: &Grid, board: &[Field]) -> BoardState {
+    for line in grid.lines() {
+        let field = board[line[0]];
+        if field != Field::Empty && line.iter().all(|&cell| board[cell] == field) {
+            let player = match field {
+                Field::X => Player::Human,
+                _ => Player::Computer,
+            };
+            return BoardState::Winner(player, (line[0], line[line.len() - 1]));
+        }
+    }
+    if !board.contains(&Field::Empty) {
+        return BoardState::Tie;
+    }
+    BoardState::InGame
+}
+
+#[cfg(test)]
+mod test {
+    #[allow(unused_imports)]
+    use super::*;
+    #[allow(unused_imports)]
+    use crate::board::Board;
+
+    /// Exhaustive search over the whole 3×3 game tree, kept as the reference the
+    /// faster `negamax` is checked and benchmarked against.
+    fn minimax(state: BoardState, board: &[Field], player: Player, depth: i64) -> Eval {
+        match state {
+            BoardState::Tie => Eval {
+                position: 0,
+                score: 0,
+            },
+            BoardState::InGame => {
+                let evaluated_moves: Vec<Eval> = board
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(i, v)| match v {
+                        Field::Empty => {
+                            let mut cloned_board = Vec::from(board).clone();
+                            let new_field = match player {
+                                Player::Human => Field::X,
+                                Player::Computer => Field::O,
+                            };
+                            cloned_board[i] = new_field;
+                            let score = minimax(
+                                check_winner(&Grid::default(), &cloned_board),
+                                &cloned_board,
+                                -player,
+                                depth + 1,
+                            )
+                            .score;
+                            Some(Eval::new(i, score))
+                        }
+                        _ => None,
+                    })
+                    .collect();
+                let mut cloned_evals = evaluated_moves.clone();
+                cloned_evals.sort();
+                match player {
+                    Player::Human => {
+                        let last = cloned_evals.last();
+                        *last.unwrap()
+                    }
+                    Player::Computer => {
+                        let first = cloned_evals.first();
+                        *first.unwrap()
+                    }
+                }
+            }
+            winner => match winner {
+                BoardState::Winner(Player::Human, _) => Eval {
+                    position: 0,
+                    score: depth - 10,
+                },
+                BoardState::Winner(Player::Computer, _x) => Eval {
+                    position: 0,
+                    score: 10 - depth,
+                },
+                _ => unreachable!(),
+            },
+        }
+    }
+
+    #[test]
+    fn top_row_human_win() {
+        let board = &[
+            Field::X,
+            Field::X,
+            Field::X,
+            Field::Empty,
+            Field::Empty,
+            Field::Empty,
+            Field::Empty,
+            Field::Empty,
+            Field::Empty,
+            Field::Empty,
+        ];
+        let actual = check_winner(&Grid::default(), board);
+        assert_eq!(
+            actual,
+            BoardState::Winner(Player::Human, (0, 2)),
+            "Human Win"
+        );
+    }
+    #[test]
+    fn mid_row_human_win() {
+        let board = &[
+            Field::Empty,
+            Field::Empty,
+            Field::Empty,
+            Field::X,
+            Field::X,
+            Field::X,
+            Field::Empty,
+            Field::Empty,
+            Field::Empty,
+        ];
+        let actual = check_winner(&Grid::default(), board);
+        assert_eq!(
+            actual,
+            BoardState::Winner(Player::Human, (3, 5)),
+            "Human Win"
+        );
+    }
+    #[test]
+    fn bot_row_human_win() {
+        let board = &[
+            Field::Empty,
+            Field::Empty,
+            Field::Empty,
+            Field::Empty,
+            Field::Empty,
+            Field::Empty,
+            Field::X,
+            Field::X,
+            Field::X,
+        ];
+        let actual = check_winner(&Grid::default(), board);
+        assert_eq!(
+            actual,
+            BoardState::Winner(Player::Human, (6, 8)),
+            "Human Win"
+        );
+    }
+    #[test]
+    fn top_row_comp_win() {
+        let board = &[
+            Field::O,
+            Field::O,
+            Field::O,
+            Field::Empty,
+            Field::Empty,
+            Field::Empty,
+            Field::Empty,
+            Field::Empty,
+            Field::Empty,
+            Field::Empty,
+        ];
+        let actual = check_winner(&Grid::default(), board);
+        assert_eq!(
+            actual,
+            BoardState::Winner(Player::Computer, (0, 2)),
+            "Computer Win"
+        );
+    }
+    #[test]
+    fn mid_row_comp_win() {
+        let board = &[
+            Field::Empty,
+            Field::Empty,
+            Field::Empty,
+            Field::O,
+            Field::O,
+            Field::O,
+            Field::Empty,
+            Field::Empty,
+            Field::Empty,
+        ];
+        let actual = check_winner(&Grid::default(), board);
+        assert_eq!(
+            actual,
+            BoardState::Winner(Player::Computer, (3, 5)),
+            "Computer Win"
+        );
+    }
+    #[test]
+    fn bot_row_comp_win() {
+        let board = &[
+            Field::Empty,
+            Field::Empty,
+            Field::Empty,
+            Field::Empty,
+            Field::Empty,
+            Field::Empty,
+            Field::O,
+            Field::O,
+            Field::O,
+        ];
+        let actual = check_winner(&Grid::default(), board);
+        assert_eq!(
+            actual,
+            BoardState::Winner(Player::Computer, (6, 8)),
+            "Computer Win"
+        );
+    }
+    #[test]
+    fn left_col_human_win() {
+        let board = &[
+            Field::X,
+            Field::Empty,
+            Field::Empty,
+            Field::X,
+            Field::Empty,
+            Field::Empty,
+            Field::X,
+            Field::Empty,
+            Field::Empty,
+        ];
+        let actual = check_winner(&Grid::default(), board);
+        assert_eq!(actual, BoardState::Winner(Player::Human, (0, 6)),);
+    }
+    #[test]
+    fn mid_col_human_win() {
+        let board = &[
+            Field::Empty,
+            Field::X,
+            Field::Empty,
+            Field::Empty,
+            Field::X,
+            Field::Empty,
+            Field::Empty,
+            Field::X,
+            Field::Empty,
+        ];
+        let actual = check_winner(&Grid::default(), board);
+        assert_eq!(actual, BoardState::Winner(Player::Human, (1, 7)),);
+    }
+    #[test]
+    fn right_col_human_win() {
+        let board = &[
+            Field::Empty,
+            Field::Empty,
+            Field::X,
+            Field::Empty,
+            Field::Empty,
+            Field::X,
+            Field::Empty,
+            Field::Empty,
+            Field::X,
+        ];
+        let actual = check_winner(&Grid::default(), board);
+        assert_eq!(actual, BoardState::Winner(Player::Human, (2, 8)),);
+    }
+    #[test]
+    fn left_col_comp_win() {
+        let board = &[
+            Field::O,
+            Field::Empty,
+            Field::Empty,
+            Field::O,
+            Field::Empty,
+            Field::Empty,
+            Field::O,
+            Field::Empty,
+            Field::Empty,
+        ];
+        let actual = check_winner(&Grid::default(), board);
+        assert_eq!(actual, BoardState::Winner(Player::Computer, (0, 6)),);
+    }
+    #[test]
+    fn mid_col_comp_win() {
+        let board = &[
+            Field::Empty,
+            Field::O,
+            Field::Empty,
+            Field::Empty,
+            Field::O,
+            Field::Empty,
+            Field::Empty,
+            Field::O,
+            Field::Empty,
+        ];
+        let actual = check_winner(&Grid::default(), board);
+        assert_eq!(actual, BoardState::Winner(Player::Computer, (1, 7)),);
+    }
+    #[test]
+    fn right_col_comp_win() {
+        let board = &[
+            Field::Empty,
+            Field::Empty,
+            Field::O,
+            Field::Empty,
+            Field::Empty,
+            Field::O,
+            Field::Empty,
+            Field::Empty,
+            Field::O,
+        ];
+        let actual = check_winner(&Grid::default(), board);
+        assert_eq!(actual, BoardState::Winner(Player::Computer, (2, 8)),);
+    }
+    #[test]
+    fn left_diag_human_win() {
+        let board = &[
+            Field::X,
+            Field::Empty,
+            Field::Empty,
+            Field::Empty,
+            Field::X,
+            Field::Empty,
+            Field::Empty,
+            Field::Empty,
+            Field::X,
+        ];
+        let actual = check_winner(&Grid::default(), board);
+        assert_eq!(actual, BoardState::Winner(Player::Human, (0, 8)),);
+    }
+    #[test]
+    fn right_diag_human_win() {
+        let board = &[
+            Field::Empty,
+            Field::Empty,
+            Field::X,
+            Field::Empty,
+            Field::X,
+            Field::Empty,
+            Field::X,
+            Field::Empty,
+            Field::Empty,
+        ];
+        let actual = check_winner(&Grid::default(), board);
+        assert_eq!(actual, BoardState::Winner(Player::Human, (2, 6)),);
+    }
+    #[test]
+    fn left_diag_comp_win() {
+        let board = &[
+            Field::O,
+            Field::Empty,
+            Field::Empty,
+            Field::Empty,
+            Field::O,
+            Field::Empty,
+            Field::Empty,
+            Field::Empty,
+            Field::O,
+        ];
+        let actual = check_winner(&Grid::default(), board);
+        assert_eq!(actual, BoardState::Winner(Player::Computer, (0, 8)),);
+    }
+    #[test]
+    fn right_diag_comp_win() {
+        let board = &[
+            Field::Empty,
+            Field::Empty,
+            Field::O,
+            Field::Empty,
+            Field::O,
+            Field::Empty,
+            Field::O,
+            Field::Empty,
+            Field::Empty,
+        ];
+        let actual = check_winner(&Grid::default(), board);
+        assert_eq!(actual, BoardState::Winner(Player::Computer, (2, 6)),);
+    }
+    #[test]
+    fn computer_optimal_play() {
+        let mut field = Field::X;
+        let mut game = Board::new(Grid::default());
+        let mut winner = None;
+        (0..90).any(|_| {
+            let eval = minimax(game.state, &game.board, game.current_player, 0);
+            game.board[eval.position] = field;
+            game.made_move();
+            match game.state {
+                BoardState::InGame => {
+                    field = -field;
+                    return false;
+                }
+                BoardState::Tie => {
+                    // game.board = (0..9).map(|_| Field::Empty).collect();
+                    game = Board::new(game.grid);
+                    return false;
+                }
+                _ => {
+                    winner = Some("winner");
+                    return true;
+                }
+            }
+        });
+        assert!(winner.is_none());
+    }
+    /// Every position reachable from an empty board with the game still on,
+    /// and the player to move.
+    #[allow(dead_code)]
+    fn positions() -> Vec<(Vec<Field>, Player)> {
+        let mut positions = vec![(vec![Field::Empty; 9], Player::Human)];
+        let mut i = 0;
+        while i < positions.len() {
+            let (board, player) = positions[i].clone();
+            i += 1;
+            for cell in (0..9).filter(|&cell| board[cell] == Field::Empty) {
+                let mut next = board.clone();
+                next[cell] = Field::from(player);
+                if check_winner(&Grid::default(), &next) == BoardState::InGame
+                    && !positions.iter().any(|(known, _)| *known == next)
+                {
+                    positions.push((next, -player));
+                }
+            }
+        }
+        positions
+    }
+    /// Nodes `minimax` visits from `board`.
+    #[allow(dead_code)]
+    fn tree_size(board: &mut Vec<Field>, player: Player) -> usize {
+        if check_winner(&Grid::default(), board) != BoardState::InGame {
+            return 1;
+        }
+        let mut nodes = 1;
+        for cell in 0..9 {
+            if board[cell] == Field::Empty {
+                board[cell] = Field::from(player);
+                nodes += tree_size(board, -player);
+                board[cell] = Field::Empty;
+            }
+        }
+        nodes
+    }
+    #[test]
+    fn negamax_agrees_with_minimax() {
+        let positions = positions();
+        assert_eq!(positions.len(), 4520);
+        let mut search = Search::new(Grid::default());
+        // Positions with fewer than three marks are left to the benchmark,
+        // `minimax` takes a while on those.
+        for (board, player) in positions.iter().skip(1 + 9 + 72).step_by(3) {
+            let expected = minimax(BoardState::InGame, board, *player, 0);
+            let eval = search.best_move(board, *player);
+            assert_eq!(eval.score, expected.score, "{:?}", board);
+            assert_eq!(board[eval.position], Field::Empty);
+            // The move played has to reach the same score.
+            let mut next = board.clone();
+            next[eval.position] = Field::from(*player);
+            let state = check_winner(&Grid::default(), &next);
+            let reply = minimax(state, &next, -*player, 1);
+            assert_eq!(reply.score, expected.score, "{:?}", board);
+        }
+    }
+    /// `cargo test --release -p tictactoe -- --ignored --nocapture`
+    #[test]
+    #[ignore]
+    fn bench_against_minimax() {
+        use std::time::Instant;
+        let empty = vec![Field::Empty; 9];
+        let mut corner = empty.clone();
+        corner[0] = Field::X;
+        for (name, board, player) in &[
+            ("empty board", &empty, Player::Human),
+            ("reply to a corner", &corner, Player::Computer),
+        ] {
+            let start = Instant::now();
+            let expected = minimax(BoardState::InGame, board, *player, 0);
+            let slow = start.elapsed();
+            let nodes = tree_size(&mut board.to_vec(), *player);
+
+            let start = Instant::now();
+            let mut search = Search::new(Grid::default());
+            let eval = search.best_move(board, *player);
+            let fast = start.elapsed();
+
+            assert_eq!(eval.score, expected.score);
+            println!(
+                "{}: minimax {} nodes in {:?}, negamax {} nodes in {:?}",
+                name, nodes, slow, search.nodes, fast
+            );
+        }
+    }
+    /// Board from rows of `X`, `O` and `.`.
+    #[allow(dead_code)]
+    fn parse(rows: &[&str]) -> Vec<Field> {
+        rows.iter()
+            .flat_map(|row| row.chars())
+            .map(|c| match c {
+                'X' => Field::X,
+                'O' => Field::O,
+                _ => Field::Empty,
+            })
+            .collect()
+    }
+    #[test]
+    fn k_in_a_row_wins() {
+        let grid = Grid::new(5, 4, 4);
+        let board = parse(&[".....", ".O...", "X.O..", "XX.O."]);
+        assert_eq!(check_winner(&grid, &board), BoardState::InGame);
+        let board = parse(&["O....", ".O...", "X.O..", "XX.O."]);
+        assert_eq!(
+            check_winner(&grid, &board),
+            BoardState::Winner(Player::Computer, (0, 18))
+        );
+    }
+    #[test]
+    fn gomoku_wins_and_blocks() {
+        let grid = Grid::new(15, 15, 5);
+        let mut board = vec![Field::Empty; grid.len()];
+        for column in 5..9 {
+            board[grid.cell(column, 7)] = Field::O;
+        }
+        board[grid.cell(4, 7)] = Field::X;
+        for column in 3..6 {
+            board[grid.cell(column, 9)] = Field::X;
+        }
+        let eval = negamax(&grid, &board, Player::Computer);
+        assert_eq!(eval.position, grid.cell(9, 7));
+        assert!(eval.score > 0);
+
+        // An open three has to be blocked at one end.
+        board[grid.cell(9, 7)] = Field::X;
+        let eval = negamax(&grid, &board, Player::Computer);
+        assert!([grid.cell(2, 9), grid.cell(6, 9)].contains(&eval.position));
+    }
+
+    #[test]
+    fn move_scores_rank_moves() {
+        let grid = Grid::default();
+        let mut search = Search::new(grid);
+        let scores = search.move_scores(&[Field::Empty; 9], Player::Human);
+        assert_eq!(scores.len(), 9);
+        assert!(scores.iter().all(|eval| eval.score == 0));
+
+        // X wins on the right and loses anywhere else.
+        let board = parse(&["XX.", "OO.", "..."]);
+        let scores = search.move_scores(&board, Player::Human);
+        assert_eq!(scores.len(), 5);
+        for eval in scores {
+            assert_eq!(eval.score < 0, eval.position == 2, "{}", eval);
+        }
+    }
+}
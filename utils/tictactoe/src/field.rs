@@ -1,3 +1,4 @@
+use crate::player::Player;
 use std::ops::Neg;
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -19,6 +20,16 @@ impl Neg for Field {
     }
 }
 
+/// The mark `player` puts down.
+impl From<Player> for Field {
+    fn from(player: Player) -> Self {
+        match player {
+            Player::Human => Field::X,
+            Player::Computer => Field::O,
+        }
+    }
+}
+
 impl std::string::ToString for Field {
     fn to_string(&self) -> String {
         match self {
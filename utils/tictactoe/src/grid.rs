@@ -0,0 +1,221 @@
+use crate::field::Field;
+
+/// Shape of an m,n,k-game: `columns` by `rows` cells, `k` in a row wins.
+/// Cells are numbered row by row from the top left.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Grid {
+    pub columns: usize,
+    pub rows: usize,
+    pub k: usize,
+}
+
+impl Default for Grid {
+    /// Tic-tac-toe.
+    fn default() -> Self {
+        Grid::new(3, 3, 3)
+    }
+}
+
+impl Grid {
+    pub fn new(columns: usize, rows: usize, k: usize) -> Self {
+        let (columns, rows) = (columns.max(1), rows.max(1));
+        Grid {
+            columns,
+            rows,
+            k: k.max(1).min(columns.max(rows)),
+        }
+    }
+
+    /// `--board CxR` and `--k N`, tic-tac-toe without them. `k` defaults to
+    /// the shorter side, at most five.
+    pub fn from_args() -> Self {
+        Self::parse(std::env::args().skip(1)).unwrap_or_else(|err| {
+            eprintln!("{}", err);
+            Grid::default()
+        })
+    }
+
+    pub fn parse<I: IntoIterator<Item = String>>(args: I) -> Result<Self, String> {
+        let (mut size, mut k) = ((3, 3), None);
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            if arg != "--board" && arg != "--k" {
+                continue;
+            }
+            let value = args.next().unwrap_or_default();
+            if arg == "--board" {
+                size = parse_size(&value).ok_or(format!("not a board: {}", value))?;
+            } else {
+                k = Some(
+                    value
+                        .parse()
+                        .map_err(|_| format!("not a length: {}", value))?,
+                );
+            }
+        }
+        let (columns, rows) = (size.0 as usize, size.1 as usize);
+        let k = k.unwrap_or_else(|| columns.min(rows).min(5));
+        Ok(Grid::new(columns, rows, k))
+    }
+
+    #[allow(clippy::len_without_is_empty)]
+    pub fn len(&self) -> usize {
+        self.columns * self.rows
+    }
+
+    pub fn cell(&self, column: usize, row: usize) -> usize {
+        row * self.columns + column
+    }
+
+    /// Column and row of `cell`.
+    pub fn position(&self, cell: usize) -> (usize, usize) {
+        (cell % self.columns, cell / self.columns)
+    }
+
+    /// Every run of `k` cells along rows, columns and both diagonals.
+    pub fn lines(&self) -> Vec<Vec<usize>> {
+        let (columns, rows, k) = (self.columns as isize, self.rows as isize, self.k as isize);
+        let mut lines = Vec::new();
+        for &(dx, dy) in &[(1, 0), (0, 1), (1, 1), (-1, 1)] {
+            for row in 0..rows {
+                for column in 0..columns {
+                    let (last_x, last_y) = (column + dx * (k - 1), row + dy * (k - 1));
+                    if last_x < 0 || last_x >= columns || last_y >= rows {
+                        continue;
+                    }
+                    let line = (0..k)
+                        .map(|i| self.cell((column + dx * i) as usize, (row + dy * i) as usize))
+                        .collect();
+                    lines.push(line);
+                }
+            }
+        }
+        lines
+    }
+
+    /// Rotations and mirrors that map the board onto itself, eight for a
+    /// square board and four otherwise. `symmetry[i]` is the cell that lands
+    /// on `i`.
+    pub fn symmetries(&self) -> Vec<Vec<usize>> {
+        let (w, h) = (self.columns - 1, self.rows - 1);
+        let count = if self.columns == self.rows { 8 } else { 4 };
+        (0..count)
+            .map(|symmetry| {
+                (0..self.len())
+                    .map(|cell| {
+                        let (x, y) = self.position(cell);
+                        let (x, y) = match symmetry {
+                            0 => (x, y),
+                            1 => (w - x, h - y),
+                            2 => (w - x, y),
+                            3 => (x, h - y),
+                            // Square boards only.
+                            4 => (y, x),
+                            5 => (w - y, h - x),
+                            6 => (w - y, x),
+                            _ => (y, h - x),
+                        };
+                        self.cell(x, y)
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+/// `CxR`, as `--size` takes it.
+fn parse_size(size: &str) -> Option<(u32, u32)> {
+    let mut parts = size.splitn(2, &['x', 'X'][..]);
+    let columns = parts.next()?.trim().parse().ok()?;
+    let rows = parts.next()?.trim().parse().ok()?;
+    Some((columns, rows))
+}
+
+/// The runs of a grid and the ones through each cell, built once per search.
+#[derive(Debug, Clone)]
+pub struct Lines {
+    pub runs: Vec<Vec<usize>>,
+    /// Indices into `runs` of the runs through every cell.
+    pub through: Vec<Vec<usize>>,
+}
+
+impl Lines {
+    pub fn new(grid: &Grid) -> Self {
+        let runs = grid.lines();
+        let mut through = vec![Vec::new(); grid.len()];
+        for (i, run) in runs.iter().enumerate() {
+            for &cell in run {
+                through[cell].push(i);
+            }
+        }
+        Lines { runs, through }
+    }
+
+    /// Whether the mark on `cell` finished a run.
+    pub fn completes(&self, board: &[Field], cell: usize) -> bool {
+        self.through[cell]
+            .iter()
+            .any(|&run| self.runs[run].iter().all(|&i| board[i] == board[cell]))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn args(args: &str) -> Vec<String> {
+        args.split_whitespace().map(String::from).collect()
+    }
+
+    #[test]
+    fn lines_of_k() {
+        assert_eq!(Grid::default().lines().len(), 8);
+        assert_eq!(Grid::new(4, 4, 4).lines().len(), 10);
+        assert_eq!(Grid::new(4, 3, 3).lines().len(), 6 + 4 + 2 + 2);
+        assert_eq!(
+            Grid::new(15, 15, 5).lines().len(),
+            2 * 15 * 11 + 2 * 11 * 11
+        );
+        assert!(Grid::new(15, 15, 5)
+            .lines()
+            .iter()
+            .all(|line| line.len() == 5));
+    }
+
+    #[test]
+    fn symmetries_keep_lines() {
+        for grid in &[Grid::default(), Grid::new(4, 3, 3), Grid::new(5, 5, 4)] {
+            let mut lines = grid.lines();
+            lines.iter_mut().for_each(|line| line.sort());
+            lines.sort();
+            let symmetries = grid.symmetries();
+            assert_eq!(
+                symmetries.len(),
+                if grid.columns == grid.rows { 8 } else { 4 }
+            );
+            for symmetry in &symmetries {
+                let mut mapped: Vec<Vec<usize>> = lines
+                    .iter()
+                    .map(|line| {
+                        let mut line: Vec<usize> = line.iter().map(|&i| symmetry[i]).collect();
+                        line.sort();
+                        line
+                    })
+                    .collect();
+                mapped.sort();
+                assert_eq!(mapped, lines);
+            }
+        }
+    }
+
+    #[test]
+    fn parses_boards() {
+        assert_eq!(Grid::parse(args("")), Ok(Grid::default()));
+        assert_eq!(Grid::parse(args("--board 15x15")), Ok(Grid::new(15, 15, 5)));
+        assert_eq!(
+            Grid::parse(args("--board 7x6 --k 4")),
+            Ok(Grid::new(7, 6, 4))
+        );
+        assert!(Grid::parse(args("--board 7")).is_err());
+    }
+}
@@ -0,0 +1,23 @@
+//! Rules and players of tic-tac-toe and other m,n,k-games, without any
+//! drawing. The sketch in `examples/tic-tac-toe` is one front end for it.
+pub mod board;
+pub mod brain;
+mod eval;
+mod field;
+pub mod grid;
+pub mod mcts;
+pub mod net;
+pub mod notation;
+mod player;
+pub mod strategy;
+pub mod tournament;
+
+pub use board::{Board, BoardState, GameMode};
+pub use eval::Eval;
+pub use field::Field;
+pub use grid::Grid;
+pub use net::{NetEvent, Remote};
+pub use notation::Game;
+pub use player::Player;
+pub use strategy::{Difficulty, Strategy};
+pub use tournament::Tournament;
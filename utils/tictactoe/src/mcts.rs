@@ -0,0 +1,261 @@
+use crate::field::Field;
+use crate::grid::{Grid, Lines};
+use crate::player::Player;
+use crate::strategy::Strategy;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::time::{Duration, Instant};
+
+#[derive(Debug)]
+struct Node {
+    /// Cell played to get here and who played it.
+    cell: usize,
+    player: Player,
+    parent: Option<usize>,
+    children: Vec<usize>,
+    untried: Vec<usize>,
+    visits: u32,
+    /// Playouts won by `player`, draws count half.
+    wins: f32,
+    /// Set once the game is over here.
+    outcome: Option<Option<Player>>,
+}
+
+/// Monte Carlo tree search with upper confidence bounds (UCT).
+///
+/// Plays random games from the position and grows a tree towards the
+/// moves that win them most often. Strength follows `playouts`, it needs
+/// no evaluation so it plays any board size.
+#[derive(Debug)]
+pub struct Mcts {
+    /// Playouts per move.
+    pub playouts: usize,
+    /// Stop thinking after this long even if playouts are left.
+    pub time: Option<Duration>,
+    /// Weight of rarely visited moves against good ones, √2 in theory.
+    pub exploration: f32,
+    rng: StdRng,
+}
+
+impl Mcts {
+    pub fn new(playouts: usize) -> Self {
+        Mcts {
+            playouts: playouts.max(1),
+            time: None,
+            exploration: 2f32.sqrt(),
+            rng: StdRng::from_entropy(),
+        }
+    }
+
+    pub fn time(mut self, time: Duration) -> Self {
+        self.time = Some(time);
+        self
+    }
+
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.rng = StdRng::seed_from_u64(seed);
+        self
+    }
+
+    /// Most visited move for `player`.
+    pub fn best_move(&mut self, grid: &Grid, board: &[Field], player: Player) -> usize {
+        let lines = Lines::new(grid);
+        let start = Instant::now();
+        let mut tree = vec![Node {
+            cell: 0,
+            player: -player,
+            parent: None,
+            children: Vec::new(),
+            untried: empty_cells(board),
+            visits: 0,
+            wins: 0.,
+            outcome: None,
+        }];
+
+        let mut cells = board.to_vec();
+        for playout in 0..self.playouts {
+            if let Some(time) = self.time {
+                if playout % 64 == 0 && start.elapsed() > time {
+                    break;
+                }
+            }
+            cells.copy_from_slice(board);
+
+            // Selection, down the tree while every move has been tried.
+            let mut node = 0;
+            while tree[node].untried.is_empty() && !tree[node].children.is_empty() {
+                node = self.select(&tree, node);
+                cells[tree[node].cell] = Field::from(tree[node].player);
+            }
+
+            // Expansion, one new move.
+            if tree[node].outcome.is_none() && !tree[node].untried.is_empty() {
+                let untried = &mut tree[node].untried;
+                let cell = untried.swap_remove(self.rng.gen_range(0, untried.len()));
+                let player = -tree[node].player;
+                cells[cell] = Field::from(player);
+                let outcome = if lines.completes(&cells, cell) {
+                    Some(Some(player))
+                } else if !cells.contains(&Field::Empty) {
+                    Some(None)
+                } else {
+                    None
+                };
+                tree.push(Node {
+                    cell,
+                    player,
+                    parent: Some(node),
+                    children: Vec::new(),
+                    untried: empty_cells(&cells),
+                    visits: 0,
+                    wins: 0.,
+                    outcome,
+                });
+                let child = tree.len() - 1;
+                tree[node].children.push(child);
+                node = child;
+            }
+
+            // Simulation.
+            let winner = match tree[node].outcome {
+                Some(outcome) => outcome,
+                None => self.playout(&lines, &mut cells, -tree[node].player),
+            };
+
+            // Backpropagation.
+            let mut next = Some(node);
+            while let Some(node) = next {
+                let node = &mut tree[node];
+                node.visits += 1;
+                node.wins += match winner {
+                    Some(winner) if winner == node.player => 1.,
+                    Some(_) => 0.,
+                    None => 0.5,
+                };
+                next = node.parent;
+            }
+        }
+
+        tree[0]
+            .children
+            .iter()
+            .max_by_key(|&&child| tree[child].visits)
+            .map(|&child| tree[child].cell)
+            .unwrap_or_else(|| tree[0].untried[0])
+    }
+
+    /// Child of `node` with the best upper confidence bound.
+    fn select(&self, tree: &[Node], node: usize) -> usize {
+        let ln = (tree[node].visits as f32).ln();
+        let bound = |child: usize| {
+            let child = &tree[child];
+            let visits = child.visits as f32;
+            child.wins / visits + self.exploration * (ln / visits).sqrt()
+        };
+        *tree[node]
+            .children
+            .iter()
+            .max_by(|&&a, &&b| bound(a).partial_cmp(&bound(b)).unwrap())
+            .unwrap()
+    }
+
+    /// Random moves until someone wins, `None` for a draw.
+    fn playout(
+        &mut self,
+        lines: &Lines,
+        cells: &mut [Field],
+        mut player: Player,
+    ) -> Option<Player> {
+        let mut empty = empty_cells(cells);
+        while !empty.is_empty() {
+            let cell = empty.swap_remove(self.rng.gen_range(0, empty.len()));
+            cells[cell] = Field::from(player);
+            if lines.completes(cells, cell) {
+                return Some(player);
+            }
+            player = -player;
+        }
+        None
+    }
+}
+
+impl Strategy for Mcts {
+    fn play(&mut self, grid: &Grid, board: &[Field], player: Player) -> usize {
+        self.best_move(grid, board, player)
+    }
+}
+
+fn empty_cells(board: &[Field]) -> Vec<usize> {
+    (0..board.len())
+        .filter(|&cell| board[cell] == Field::Empty)
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::board::BoardState;
+    use crate::brain::check_winner;
+
+    /// Plays `games` of MCTS against random moves, MCTS going first in every
+    /// other game, and returns its losses.
+    fn losses_to_random(playouts: usize, games: u64) -> usize {
+        let grid = Grid::default();
+        let mut losses = 0;
+        for game in 0..games {
+            let mut mcts = Mcts::new(playouts).seed(game);
+            let mut rng = StdRng::seed_from_u64(game + 1000);
+            let mut board = vec![Field::Empty; grid.len()];
+            let mut player = if game % 2 == 0 {
+                Player::Computer
+            } else {
+                Player::Human
+            };
+            while check_winner(&grid, &board) == BoardState::InGame {
+                let cell = match player {
+                    Player::Computer => mcts.best_move(&grid, &board, player),
+                    Player::Human => {
+                        let empty = empty_cells(&board);
+                        empty[rng.gen_range(0, empty.len())]
+                    }
+                };
+                assert_eq!(board[cell], Field::Empty);
+                board[cell] = Field::from(player);
+                player = -player;
+            }
+            if let BoardState::Winner(Player::Human, _) = check_winner(&grid, &board) {
+                losses += 1;
+            }
+        }
+        losses
+    }
+
+    #[test]
+    fn never_loses_to_random_play() {
+        assert_eq!(losses_to_random(2000, 60), 0);
+    }
+
+    #[test]
+    fn takes_a_win_and_blocks_one() {
+        let grid = Grid::default();
+        let (x, o, e) = (Field::X, Field::O, Field::Empty);
+        let mut mcts = Mcts::new(2000).seed(7);
+        let board = [o, o, e, x, x, e, x, e, e];
+        assert_eq!(mcts.best_move(&grid, &board, Player::Computer), 2);
+        let board = [x, x, e, e, o, e, e, e, e];
+        assert_eq!(mcts.best_move(&grid, &board, Player::Computer), 2);
+    }
+
+    #[test]
+    fn stops_in_time() {
+        let grid = Grid::new(15, 15, 5);
+        let board = vec![Field::Empty; grid.len()];
+        let mut mcts = Mcts::new(usize::MAX)
+            .time(Duration::from_millis(50))
+            .seed(1);
+        let start = Instant::now();
+        let cell = mcts.best_move(&grid, &board, Player::Computer);
+        assert!(start.elapsed() < Duration::from_secs(1));
+        assert!(cell < grid.len());
+    }
+}
@@ -0,0 +1,686 @@
+//! Two instances playing over TCP, one hosting and one connecting.
+//!
+//! Both sides send lines of text:
+//!
+//! - `HELLO 1 3x3 k3`, the protocol version and the board, first thing on a
+//!   new connection.
+//! - `GAME b2 a1`, the host's moves so far, after `HELLO` and whenever the
+//!   two sides disagree. The guest takes them over.
+//! - `MOVE 2 c3 9b1c...`, a move with the number of moves before it and a
+//!   digest of the game after it.
+//! - `SYNC`, the guest asking for `GAME`.
+//! - `NEW`, starting over.
+//! - `ERROR text`, the other side gives up on the connection.
+//!
+//! The host plays X and moves first. It keeps listening so a guest that lost
+//! the connection can come back, and the guest keeps trying to reach it.
+use crate::board::{Board, BoardState};
+use crate::grid::Grid;
+use crate::notation::{self, cell_name};
+use crate::player::Player;
+use std::fmt;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{Shutdown, SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+use std::thread;
+use std::time::Duration;
+
+const VERSION: u32 = 1;
+/// Pause between attempts to reach the host.
+const RETRY: Duration = Duration::from_millis(250);
+
+/// What happened on the connection since the last `poll`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NetEvent {
+    Connected,
+    /// The connection dropped, the guest is reconnecting.
+    Disconnected,
+    /// The other side played this cell.
+    Moved(usize),
+    /// The board was replaced by the host's game.
+    Synced,
+    NewGame,
+    /// The two sides disagreed about the game, a `GAME` from the host
+    /// settles it.
+    Desync(String),
+    /// The other side can't play with this one.
+    Error(String),
+}
+
+impl fmt::Display for NetEvent {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            NetEvent::Connected => write!(f, "connected"),
+            NetEvent::Disconnected => write!(f, "connection lost"),
+            NetEvent::Moved(_) => write!(f, "they moved"),
+            NetEvent::Synced => write!(f, "caught up with the host"),
+            NetEvent::NewGame => write!(f, "new game"),
+            NetEvent::Desync(reason) => write!(f, "out of step, {}", reason),
+            NetEvent::Error(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Message {
+    Hello {
+        version: u32,
+        grid: Grid,
+    },
+    Game(Vec<usize>),
+    Move {
+        ply: usize,
+        cell: usize,
+        digest: u64,
+    },
+    Sync,
+    New,
+    Error(String),
+}
+
+impl Message {
+    /// Cells are named on `grid`, the board both sides play on.
+    fn parse(line: &str, grid: &Grid) -> Result<Self, String> {
+        let cell =
+            |name: &str| notation::parse_cell(grid, name).ok_or(format!("not a cell: {}", name));
+        let number = |word: Option<&str>| {
+            word.and_then(|word| word.parse::<usize>().ok())
+                .ok_or(format!("bad message: {}", line))
+        };
+        let mut words = line.split_whitespace();
+        let message = match words.next() {
+            Some("HELLO") => {
+                let version = number(words.next())? as u32;
+                let grid = notation::parse_header(&words.collect::<Vec<_>>().join(" "))?;
+                Message::Hello { version, grid }
+            }
+            Some("GAME") => Message::Game(words.map(cell).collect::<Result<_, _>>()?),
+            Some("MOVE") => {
+                let ply = number(words.next())?;
+                let cell = cell(words.next().unwrap_or(""))?;
+                let digest = words
+                    .next()
+                    .and_then(|digest| u64::from_str_radix(digest, 16).ok())
+                    .ok_or(format!("bad message: {}", line))?;
+                Message::Move { ply, cell, digest }
+            }
+            Some("SYNC") => Message::Sync,
+            Some("NEW") => Message::New,
+            Some("ERROR") => Message::Error(line.trim()["ERROR".len()..].trim().to_string()),
+            _ => return Err(format!("unknown message: {}", line)),
+        };
+        Ok(message)
+    }
+
+    fn to_line(&self, grid: &Grid) -> String {
+        match self {
+            Message::Hello { version, grid } => {
+                format!("HELLO {} {}", version, notation::header(grid))
+            }
+            Message::Game(moves) => {
+                let mut words = vec!["GAME".to_string()];
+                words.extend(moves.iter().map(|&cell| cell_name(grid, cell)));
+                words.join(" ")
+            }
+            Message::Move { ply, cell, digest } => {
+                format!("MOVE {} {} {:016x}", ply, cell_name(grid, *cell), digest)
+            }
+            Message::Sync => "SYNC".to_string(),
+            Message::New => "NEW".to_string(),
+            Message::Error(text) => format!("ERROR {}", text),
+        }
+    }
+}
+
+/// FNV-1a of the moves, both sides have to arrive at the same one.
+fn digest(moves: &[usize]) -> u64 {
+    moves
+        .iter()
+        .flat_map(|&cell| (cell as u32).to_le_bytes().to_vec())
+        .fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3)
+        })
+}
+
+/// An open connection, a thread reads its lines.
+#[derive(Debug)]
+struct Link {
+    stream: TcpStream,
+    /// `None` once the other side is gone.
+    lines: Receiver<Option<String>>,
+}
+
+impl Link {
+    fn new(stream: TcpStream) -> io::Result<Self> {
+        let reader = BufReader::new(stream.try_clone()?);
+        let (sender, lines) = mpsc::channel();
+        thread::spawn(move || {
+            for line in reader.lines() {
+                let line = match line {
+                    Ok(line) => line,
+                    Err(_) => break,
+                };
+                if sender.send(Some(line)).is_err() {
+                    return;
+                }
+            }
+            let _ = sender.send(None);
+        });
+        Ok(Link { stream, lines })
+    }
+}
+
+impl Drop for Link {
+    fn drop(&mut self) {
+        let _ = self.stream.shutdown(Shutdown::Both);
+    }
+}
+
+/// The other player, across the network.
+///
+/// Front ends `poll` it every frame, which applies the other side's moves to
+/// their board, and make their own moves through `play`.
+#[derive(Debug)]
+pub struct Remote {
+    /// The mark played on this side, X on the host.
+    pub side: Player,
+    grid: Grid,
+    /// Connections accepted by the host or made by the guest.
+    incoming: Receiver<TcpStream>,
+    link: Option<Link>,
+    /// Where the host listens.
+    addr: SocketAddr,
+    /// Hands the guest's new connections to `incoming`.
+    dialer: Option<Sender<TcpStream>>,
+}
+
+impl Remote {
+    /// Listens on `addr` for a guest, port 0 picks a free one.
+    pub fn host<A: ToSocketAddrs>(addr: A, grid: Grid) -> io::Result<Self> {
+        let listener = TcpListener::bind(addr)?;
+        let addr = listener.local_addr()?;
+        let (sender, incoming) = mpsc::channel();
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                if sender.send(stream).is_err() {
+                    break;
+                }
+            }
+        });
+        Ok(Remote {
+            side: Player::Human,
+            grid,
+            incoming,
+            link: None,
+            addr,
+            dialer: None,
+        })
+    }
+
+    /// Plays O against the host at `addr`, who may not be up yet.
+    pub fn connect<A: ToSocketAddrs>(addr: A, grid: Grid) -> io::Result<Self> {
+        let addr = addr
+            .to_socket_addrs()?
+            .next()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "no address"))?;
+        let (sender, incoming) = mpsc::channel();
+        let remote = Remote {
+            side: Player::Computer,
+            grid,
+            incoming,
+            link: None,
+            addr,
+            dialer: Some(sender),
+        };
+        remote.dial();
+        Ok(remote)
+    }
+
+    /// Where the host listens.
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    pub fn is_host(&self) -> bool {
+        self.dialer.is_none()
+    }
+
+    pub fn is_connected(&self) -> bool {
+        self.link.is_some()
+    }
+
+    pub fn my_turn(&self, board: &Board) -> bool {
+        self.is_connected()
+            && board.state == BoardState::InGame
+            && board.current_player == self.side
+    }
+
+    /// One line for the front end to show.
+    pub fn status(&self, board: &Board) -> &'static str {
+        if !self.is_connected() {
+            if self.is_host() {
+                "waiting for the other player"
+            } else {
+                "connecting"
+            }
+        } else if board.state != BoardState::InGame {
+            "game over"
+        } else if self.my_turn(board) {
+            "your move"
+        } else {
+            "their move"
+        }
+    }
+
+    /// Plays `cell` on `board` and sends it, `false` if it isn't this
+    /// side's turn or not a legal move.
+    pub fn play(&mut self, board: &mut Board, cell: usize) -> bool {
+        if !self.my_turn(board) || !board.play(cell) {
+            return false;
+        }
+        let ply = board.history.len() - 1;
+        let digest = digest(&board.history);
+        self.send(&Message::Move { ply, cell, digest });
+        true
+    }
+
+    /// Clears `board` on both sides.
+    pub fn new_game(&mut self, board: &mut Board) {
+        *board = Board::new(self.grid);
+        self.send(&Message::New);
+    }
+
+    /// Takes in new connections and the other side's messages.
+    pub fn poll(&mut self, board: &mut Board) -> Vec<NetEvent> {
+        let mut events = Vec::new();
+        while let Ok(stream) = self.incoming.try_recv() {
+            match Link::new(stream) {
+                Ok(link) => {
+                    self.link = Some(link);
+                    events.push(NetEvent::Connected);
+                    let grid = self.grid;
+                    self.send(&Message::Hello {
+                        version: VERSION,
+                        grid,
+                    });
+                    if self.is_host() {
+                        self.send(&Message::Game(board.history.clone()));
+                    }
+                }
+                Err(err) => events.push(NetEvent::Error(err.to_string())),
+            }
+        }
+        while let Some(link) = &self.link {
+            let line = match link.lines.try_recv() {
+                Ok(Some(line)) => line,
+                Err(TryRecvError::Empty) => break,
+                Ok(None) | Err(TryRecvError::Disconnected) => {
+                    self.disconnect();
+                    events.push(NetEvent::Disconnected);
+                    break;
+                }
+            };
+            events.extend(self.receive(&line, board));
+        }
+        events
+    }
+
+    fn receive(&mut self, line: &str, board: &mut Board) -> Option<NetEvent> {
+        let message = match Message::parse(line, &self.grid) {
+            Ok(message) => message,
+            Err(err) => return Some(self.desync(board, err)),
+        };
+        match message {
+            Message::Hello { version, grid } => {
+                if version == VERSION && grid == self.grid {
+                    return None;
+                }
+                let err = format!(
+                    "the other side plays {} with version {}, this one {} with version {}",
+                    notation::header(&grid),
+                    version,
+                    notation::header(&self.grid),
+                    VERSION
+                );
+                self.send(&Message::Error(err.clone()));
+                self.disconnect();
+                Some(NetEvent::Error(err))
+            }
+            Message::Game(moves) if !self.is_host() => {
+                let mut synced = Board::new(self.grid);
+                if !moves.iter().all(|&cell| synced.play(cell)) {
+                    // The host's game is the reference, there is nothing
+                    // left to sync to.
+                    self.disconnect();
+                    return Some(NetEvent::Error(format!("illegal game: {}", line)));
+                }
+                *board = synced;
+                Some(NetEvent::Synced)
+            }
+            Message::Move {
+                ply,
+                cell,
+                digest: theirs,
+            } => {
+                if ply != board.history.len() {
+                    let err = format!("move {} arrived, expected {}", ply, board.history.len());
+                    return Some(self.desync(board, err));
+                }
+                if board.current_player == self.side {
+                    return Some(self.desync(board, "move out of turn".to_string()));
+                }
+                // Only taken on once both sides agree on the result.
+                let mut next = board.clone();
+                if !next.play(cell) {
+                    let err = format!("illegal move {}", cell_name(&self.grid, cell));
+                    return Some(self.desync(board, err));
+                }
+                if digest(&next.history) != theirs {
+                    return Some(self.desync(board, "games differ".to_string()));
+                }
+                *board = next;
+                Some(NetEvent::Moved(cell))
+            }
+            Message::Sync if self.is_host() => {
+                self.send(&Message::Game(board.history.clone()));
+                None
+            }
+            Message::New => {
+                *board = Board::new(self.grid);
+                Some(NetEvent::NewGame)
+            }
+            Message::Error(err) => Some(NetEvent::Error(err)),
+            // GAME to the host or SYNC to the guest.
+            _ => Some(self.desync(board, format!("unexpected {}", line))),
+        }
+    }
+
+    /// The host's game wins, the guest asks for it.
+    fn desync(&mut self, board: &Board, reason: String) -> NetEvent {
+        if self.is_host() {
+            self.send(&Message::Game(board.history.clone()));
+        } else {
+            self.send(&Message::Sync);
+        }
+        NetEvent::Desync(reason)
+    }
+
+    fn send(&mut self, message: &Message) {
+        let line = message.to_line(&self.grid);
+        let sent = match &mut self.link {
+            Some(link) => writeln!(link.stream, "{}", line).is_ok(),
+            None => return,
+        };
+        // The reader notices too and `poll` reports it.
+        if !sent {
+            let _ = self
+                .link
+                .as_ref()
+                .map(|link| link.stream.shutdown(Shutdown::Both));
+        }
+    }
+
+    fn disconnect(&mut self) {
+        self.link = None;
+        self.dial();
+    }
+
+    /// Tries to reach the host until it answers, guests only.
+    fn dial(&self) {
+        if let Some(sender) = &self.dialer {
+            let (sender, addr) = (sender.clone(), self.addr);
+            thread::spawn(move || loop {
+                match TcpStream::connect(addr) {
+                    Ok(stream) => {
+                        let _ = sender.send(stream);
+                        break;
+                    }
+                    Err(_) => thread::sleep(RETRY),
+                }
+            });
+        }
+    }
+}
+
+/// `--host ADDR` waits for a guest on ADDR and `--connect ADDR` joins the
+/// host there, `None` without either.
+pub fn from_args(grid: Grid) -> Option<Remote> {
+    parse(std::env::args().skip(1), grid).unwrap_or_else(|err| {
+        eprintln!("{}", err);
+        None
+    })
+}
+
+pub fn parse<I: IntoIterator<Item = String>>(
+    args: I,
+    grid: Grid,
+) -> Result<Option<Remote>, String> {
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        if arg != "--host" && arg != "--connect" {
+            continue;
+        }
+        let addr = args.next().unwrap_or_default();
+        let remote = if arg == "--host" {
+            Remote::host(addr.as_str(), grid)
+        } else {
+            Remote::connect(addr.as_str(), grid)
+        };
+        return remote
+            .map(Some)
+            .map_err(|err| format!("can't {} {}: {}", &arg[2..], addr, err));
+    }
+    Ok(None)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::time::Instant;
+
+    /// Polls until `done` or a second has passed.
+    fn poll_until<F>(remote: &mut Remote, board: &mut Board, done: F) -> Vec<NetEvent>
+    where
+        F: Fn(&NetEvent) -> bool,
+    {
+        let start = Instant::now();
+        let mut events = Vec::new();
+        while start.elapsed() < Duration::from_secs(1) {
+            events.extend(remote.poll(board));
+            if events.iter().any(&done) {
+                return events;
+            }
+            thread::sleep(Duration::from_millis(5));
+        }
+        panic!("gave up waiting, got {:?}", events);
+    }
+
+    /// A peer speaking the protocol by hand.
+    struct StandIn {
+        stream: TcpStream,
+        reader: BufReader<TcpStream>,
+    }
+
+    impl StandIn {
+        fn new(stream: TcpStream) -> Self {
+            stream
+                .set_read_timeout(Some(Duration::from_secs(1)))
+                .unwrap();
+            let reader = BufReader::new(stream.try_clone().unwrap());
+            StandIn { stream, reader }
+        }
+
+        fn send(&mut self, line: &str) {
+            writeln!(self.stream, "{}", line).unwrap();
+        }
+
+        fn read(&mut self) -> String {
+            let mut line = String::new();
+            self.reader.read_line(&mut line).unwrap();
+            line.trim().to_string()
+        }
+    }
+
+    #[test]
+    fn messages_round_trip() {
+        let grid = Grid::default();
+        for message in &[
+            Message::Hello {
+                version: 1,
+                grid: Grid::new(15, 15, 5),
+            },
+            Message::Game(vec![]),
+            Message::Game(vec![4, 0, 8]),
+            Message::Move {
+                ply: 2,
+                cell: 8,
+                digest: digest(&[4, 0, 8]),
+            },
+            Message::Sync,
+            Message::New,
+            Message::Error("bye now".to_string()),
+        ] {
+            let line = message.to_line(&grid);
+            assert_eq!(
+                Message::parse(&line, &grid).as_ref(),
+                Ok(message),
+                "{}",
+                line
+            );
+        }
+        assert_eq!(
+            Message::Game(vec![4, 0]).to_line(&grid),
+            "GAME b2 a1".to_string()
+        );
+        assert!(Message::parse("MOVE 1 d4 00", &grid).is_err());
+        assert!(Message::parse("JUMP", &grid).is_err());
+        assert_ne!(digest(&[4, 0]), digest(&[0, 4]));
+    }
+
+    #[test]
+    fn host_and_guest_play() {
+        let grid = Grid::default();
+        let mut host = Remote::host("127.0.0.1:0", grid).unwrap();
+        let mut guest = Remote::connect(host.addr(), grid).unwrap();
+        let (mut ours, mut theirs) = (Board::new(grid), Board::new(grid));
+        poll_until(&mut host, &mut ours, |event| *event == NetEvent::Connected);
+        poll_until(&mut guest, &mut theirs, |event| *event == NetEvent::Synced);
+
+        assert!(!guest.play(&mut theirs, 0), "X moves first");
+        assert!(host.play(&mut ours, 4));
+        assert!(!host.play(&mut ours, 0), "one move a turn");
+        poll_until(&mut guest, &mut theirs, |event| {
+            *event == NetEvent::Moved(4)
+        });
+        assert!(guest.play(&mut theirs, 0));
+        poll_until(&mut host, &mut ours, |event| *event == NetEvent::Moved(0));
+        assert_eq!(ours.history, theirs.history);
+
+        host.new_game(&mut ours);
+        poll_until(&mut guest, &mut theirs, |event| *event == NetEvent::NewGame);
+        assert!(theirs.history.is_empty());
+    }
+
+    #[test]
+    fn host_settles_a_desync() {
+        let grid = Grid::default();
+        let mut host = Remote::host("127.0.0.1:0", grid).unwrap();
+        let mut board = Board::new(grid);
+        let mut peer = StandIn::new(TcpStream::connect(host.addr()).unwrap());
+        poll_until(&mut host, &mut board, |event| *event == NetEvent::Connected);
+        assert_eq!(peer.read(), "HELLO 1 3x3 k3");
+        assert_eq!(peer.read(), "GAME");
+        peer.send("HELLO 1 3x3 k3");
+
+        // Out of turn, X hasn't moved.
+        peer.send(&format!("MOVE 0 a1 {:016x}", digest(&[0])));
+        let events = poll_until(&mut host, &mut board, |event| {
+            matches!(event, NetEvent::Desync(_))
+        });
+        assert!(events.contains(&NetEvent::Desync("move out of turn".to_string())));
+        assert_eq!(peer.read(), "GAME");
+        assert!(board.history.is_empty());
+
+        host.play(&mut board, 4);
+        assert!(peer.read().starts_with("MOVE 0 b2 "));
+        peer.send("MOVE 1 a1 0123");
+        poll_until(&mut host, &mut board, |event| {
+            *event == NetEvent::Desync("games differ".to_string())
+        });
+        assert_eq!(peer.read(), "GAME b2");
+        assert_eq!(board.history, vec![4]);
+        peer.send("SYNC");
+        thread::sleep(Duration::from_millis(50));
+        assert!(host.poll(&mut board).is_empty());
+        assert_eq!(peer.read(), "GAME b2");
+    }
+
+    #[test]
+    fn guests_reconnect() {
+        let grid = Grid::default();
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let mut guest = Remote::connect(listener.local_addr().unwrap(), grid).unwrap();
+        let mut board = Board::new(grid);
+
+        let mut host = StandIn::new(listener.accept().unwrap().0);
+        host.send("HELLO 1 3x3 k3");
+        host.send("GAME b2");
+        poll_until(&mut guest, &mut board, |event| *event == NetEvent::Synced);
+        assert_eq!(host.read(), "HELLO 1 3x3 k3");
+        assert!(guest.play(&mut board, 0));
+        drop(host);
+        poll_until(&mut guest, &mut board, |event| {
+            *event == NetEvent::Disconnected
+        });
+
+        // The move never arrived, the host's game stands.
+        let mut host = StandIn::new(listener.accept().unwrap().0);
+        host.send("HELLO 1 3x3 k3");
+        host.send("GAME b2");
+        poll_until(&mut guest, &mut board, |event| *event == NetEvent::Synced);
+        assert_eq!(board.history, vec![4]);
+        assert!(guest.is_connected() && guest.my_turn(&board));
+    }
+
+    #[test]
+    fn guests_keep_their_board_until_it_checks_out() {
+        let grid = Grid::default();
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let mut guest = Remote::connect(listener.local_addr().unwrap(), grid).unwrap();
+        let mut board = Board::new(grid);
+
+        let mut host = StandIn::new(listener.accept().unwrap().0);
+        host.send("HELLO 1 3x3 k3");
+        host.send("GAME");
+        poll_until(&mut guest, &mut board, |event| *event == NetEvent::Synced);
+        assert_eq!(host.read(), "HELLO 1 3x3 k3");
+        host.send("MOVE 0 b2 0123");
+        poll_until(&mut guest, &mut board, |event| {
+            *event == NetEvent::Desync("games differ".to_string())
+        });
+        assert!(board.history.is_empty());
+        assert_eq!(host.read(), "SYNC");
+
+        // X twice in one cell, the guest gives up on this host.
+        host.send("GAME b2 b2");
+        poll_until(&mut guest, &mut board, |event| {
+            matches!(event, NetEvent::Error(_))
+        });
+        assert!(!guest.is_connected());
+        assert!(board.history.is_empty());
+    }
+
+    #[test]
+    fn other_boards_are_refused() {
+        let mut host = Remote::host("127.0.0.1:0", Grid::default()).unwrap();
+        let mut board = Board::new(Grid::default());
+        let mut peer = StandIn::new(TcpStream::connect(host.addr()).unwrap());
+        peer.send("HELLO 1 15x15 k5");
+        let events = poll_until(&mut host, &mut board, |event| {
+            matches!(event, NetEvent::Error(_))
+        });
+        assert!(events.contains(&NetEvent::Connected));
+        assert!(!host.is_connected());
+        assert!(peer.read().starts_with("HELLO"));
+    }
+}
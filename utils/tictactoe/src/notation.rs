@@ -0,0 +1,175 @@
+//! Games as text, the board size and then the moves in columns `a`, `b`, ...
+//! and rows `1`, `2`, ... from the top left, X moving first:
+//!
+//! ```text
+//! 3x3 k3
+//! 1. b2 a1
+//! 2. c3 c1
+//! ```
+//!
+//! Move numbers are optional and `#` starts a comment.
+use crate::board::BoardState;
+use crate::brain::check_winner;
+use crate::field::Field;
+use crate::grid::Grid;
+use crate::player::Player;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+/// A grid and the cells played on it, the human's X first.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Game {
+    pub grid: Grid,
+    pub moves: Vec<usize>,
+}
+
+impl Game {
+    pub fn new(grid: Grid) -> Self {
+        Game {
+            grid,
+            moves: Vec::new(),
+        }
+    }
+
+    /// Fields after all moves.
+    pub fn board(&self) -> Vec<Field> {
+        let mut board = vec![Field::Empty; self.grid.len()];
+        let mut player = Player::Human;
+        for &cell in &self.moves {
+            board[cell] = Field::from(player);
+            player = -player;
+        }
+        board
+    }
+
+    pub fn to_move(&self) -> Player {
+        [Player::Human, Player::Computer][self.moves.len() % 2]
+    }
+
+    /// The game in `--game FILE`, `None` without it.
+    pub fn from_args() -> Option<Self> {
+        let path = std::env::args().skip_while(|arg| arg != "--game").nth(1)?;
+        Self::load(path).map_err(|err| eprintln!("{}", err)).ok()
+    }
+
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut lines = text
+            .lines()
+            .map(|line| line.split('#').next().unwrap_or("").trim())
+            .filter(|line| !line.is_empty());
+        let header = lines.next().ok_or("empty game")?;
+        let mut game = Game::new(parse_header(header)?);
+        let mut board = game.board();
+        for token in lines.flat_map(|line| line.split_whitespace()) {
+            if token.ends_with('.') {
+                continue;
+            }
+            let cell = parse_cell(&game.grid, token).ok_or(format!("not a cell: {}", token))?;
+            if check_winner(&game.grid, &board) != BoardState::InGame {
+                return Err(format!("{} is played after the game ended", token));
+            }
+            if board[cell] != Field::Empty {
+                return Err(format!("{} is taken", token));
+            }
+            board[cell] = Field::from(game.to_move());
+            game.moves.push(cell);
+        }
+        Ok(game)
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, String> {
+        let text = fs::read_to_string(&path)
+            .map_err(|err| format!("can't read {}: {}", path.as_ref().display(), err))?;
+        Self::parse(&text)
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> std::io::Result<()> {
+        fs::write(path, self.to_string())
+    }
+}
+
+impl fmt::Display for Game {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let grid = &self.grid;
+        writeln!(f, "{}", header(grid))?;
+        for (i, pair) in self.moves.chunks(2).enumerate() {
+            let pair: Vec<String> = pair.iter().map(|&cell| cell_name(grid, cell)).collect();
+            writeln!(f, "{}. {}", i + 1, pair.join(" "))?;
+        }
+        Ok(())
+    }
+}
+
+/// `a1` for the top left cell. Columns past `z` aren't named.
+pub fn cell_name(grid: &Grid, cell: usize) -> String {
+    let (column, row) = grid.position(cell);
+    format!("{}{}", (b'a' + column as u8) as char, row + 1)
+}
+
+pub(crate) fn parse_cell(grid: &Grid, name: &str) -> Option<usize> {
+    let mut chars = name.chars();
+    let column = chars.next()?.to_ascii_lowercase();
+    if !column.is_ascii_lowercase() {
+        return None;
+    }
+    let column = (column as u8 - b'a') as usize;
+    let row = chars.as_str().parse::<usize>().ok()?.checked_sub(1)?;
+    if column < grid.columns && row < grid.rows {
+        Some(grid.cell(column, row))
+    } else {
+        None
+    }
+}
+
+pub(crate) fn header(grid: &Grid) -> String {
+    format!("{}x{} k{}", grid.columns, grid.rows, grid.k)
+}
+
+/// `CxR kK`, `k` as for `--k` if left out.
+pub(crate) fn parse_header(header: &str) -> Result<Grid, String> {
+    let mut args = vec!["--board".to_string()];
+    for part in header.split_whitespace() {
+        match part.strip_prefix('k') {
+            Some(k) => args.extend(vec!["--k".to_string(), k.to_string()]),
+            None => args.push(part.to_string()),
+        }
+    }
+    Grid::parse(args).map_err(|err| format!("bad header `{}`, {}", header, err))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::strategy::{Perfect, Strategy};
+
+    #[test]
+    fn games_round_trip() {
+        let game = Game::parse("3x3 k3\n1. b2 a1\n2. c3 c1 # O blocks\n3. b1").unwrap();
+        assert_eq!(game.moves, vec![4, 0, 8, 2, 1]);
+        assert_eq!(game.to_move(), Player::Computer);
+        assert_eq!(game.to_string(), "3x3 k3\n1. b2 a1\n2. c3 c1\n3. b1\n");
+        assert_eq!(Game::parse(&game.to_string()), Ok(game));
+
+        let gomoku = Game::parse("15x15\nh8 h9 o15").unwrap();
+        assert_eq!(gomoku.grid, Grid::new(15, 15, 5));
+        assert_eq!(gomoku.moves, vec![112, 127, 224]);
+    }
+
+    #[test]
+    fn bad_games_are_refused() {
+        assert!(Game::parse("").is_err());
+        assert!(Game::parse("3 k3\na1").is_err());
+        assert!(Game::parse("3x3\nd1").is_err());
+        assert!(Game::parse("3x3\na1 a1").is_err());
+        assert!(Game::parse("3x3\na1 b1 a2 b2 a3 b3").is_err());
+    }
+
+    #[test]
+    fn positions_load_as_tests() {
+        // X holds opposite corners, O has to answer on an edge.
+        let game = Game::parse("3x3 k3\n1. a1 b2\n2. c3").unwrap();
+        let cell = Perfect.play(&game.grid, &game.board(), game.to_move());
+        assert!(["b1", "a2", "c2", "b3"].contains(&cell_name(&game.grid, cell).as_str()));
+    }
+}
@@ -0,0 +1,380 @@
+use crate::brain::{self, Search};
+use crate::field::Field;
+use crate::grid::{Grid, Lines};
+use crate::mcts::Mcts;
+use crate::player::Player;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::fmt;
+use std::time::Duration;
+
+/// How the computer picks its moves.
+pub trait Strategy: fmt::Debug {
+    /// Empty cell to play for `player`, the game is still on.
+    fn play(&mut self, grid: &Grid, board: &[Field], player: Player) -> usize;
+}
+
+fn random_cell(rng: &mut StdRng, board: &[Field]) -> usize {
+    let empty: Vec<usize> = (0..board.len())
+        .filter(|&cell| board[cell] == Field::Empty)
+        .collect();
+    empty[rng.gen_range(0, empty.len())]
+}
+
+/// Any empty cell.
+#[derive(Debug)]
+pub struct Random {
+    rng: StdRng,
+}
+
+impl Random {
+    pub fn new() -> Self {
+        Random {
+            rng: StdRng::from_entropy(),
+        }
+    }
+
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.rng = StdRng::seed_from_u64(seed);
+        self
+    }
+}
+
+impl Default for Random {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Strategy for Random {
+    fn play(&mut self, _grid: &Grid, board: &[Field], _player: Player) -> usize {
+        random_cell(&mut self.rng, board)
+    }
+}
+
+/// Wins when it can, blocks a win of the other player, plays randomly
+/// otherwise.
+#[derive(Debug)]
+pub struct Greedy {
+    rng: StdRng,
+}
+
+impl Greedy {
+    pub fn new() -> Self {
+        Greedy {
+            rng: StdRng::from_entropy(),
+        }
+    }
+
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.rng = StdRng::seed_from_u64(seed);
+        self
+    }
+}
+
+impl Default for Greedy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Strategy for Greedy {
+    fn play(&mut self, grid: &Grid, board: &[Field], player: Player) -> usize {
+        let lines = Lines::new(grid);
+        let mut cells = board.to_vec();
+        for &player in &[player, -player] {
+            for cell in 0..cells.len() {
+                if cells[cell] != Field::Empty {
+                    continue;
+                }
+                cells[cell] = Field::from(player);
+                let completes = lines.completes(&cells, cell);
+                cells[cell] = Field::Empty;
+                if completes {
+                    return cell;
+                }
+            }
+        }
+        random_cell(&mut self.rng, board)
+    }
+}
+
+/// `brain::Search` looking `depth` plies ahead.
+#[derive(Debug)]
+pub struct Minimax {
+    pub depth: usize,
+}
+
+impl Strategy for Minimax {
+    fn play(&mut self, grid: &Grid, board: &[Field], player: Player) -> usize {
+        Search::new(*grid)
+            .depth(Some(self.depth))
+            .best_move(board, player)
+            .position
+    }
+}
+
+/// `brain::negamax`, to the end of the game on boards up to 3×3 and as deep
+/// as stays responsive on larger ones.
+#[derive(Debug, Default)]
+pub struct Perfect;
+
+impl Strategy for Perfect {
+    fn play(&mut self, grid: &Grid, board: &[Field], player: Player) -> usize {
+        brain::negamax(grid, board, player).position
+    }
+}
+
+/// Plays a random move instead of asking `strategy` with probability
+/// `epsilon`.
+#[derive(Debug)]
+pub struct Mistakes {
+    strategy: Box<dyn Strategy>,
+    epsilon: f32,
+    rng: StdRng,
+}
+
+impl Mistakes {
+    pub fn new(strategy: Box<dyn Strategy>, epsilon: f32) -> Self {
+        Mistakes {
+            strategy,
+            epsilon: epsilon.clamp(0., 1.),
+            rng: StdRng::from_entropy(),
+        }
+    }
+
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.rng = StdRng::seed_from_u64(seed);
+        self
+    }
+}
+
+impl Strategy for Mistakes {
+    fn play(&mut self, grid: &Grid, board: &[Field], player: Player) -> usize {
+        if self.rng.gen::<f32>() < self.epsilon {
+            random_cell(&mut self.rng, board)
+        } else {
+            self.strategy.play(grid, board, player)
+        }
+    }
+}
+
+/// Levels picked on the menu.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Difficulty {
+    Easy,
+    Medium,
+    Hard,
+    Perfect,
+}
+
+impl Difficulty {
+    pub fn strategy(self) -> Box<dyn Strategy> {
+        self.seeded(rand::random())
+    }
+
+    /// The same with its mistakes seeded.
+    pub fn seeded(self, seed: u64) -> Box<dyn Strategy> {
+        let (strategy, epsilon): (Box<dyn Strategy>, f32) = match self {
+            Difficulty::Easy => (Box::new(Greedy::new().seed(seed.wrapping_add(1))), 0.5),
+            Difficulty::Medium => (Box::new(Minimax { depth: 2 }), 0.2),
+            Difficulty::Hard => (Box::new(Perfect), 0.05),
+            Difficulty::Perfect => return Box::new(Perfect),
+        };
+        Box::new(Mistakes::new(strategy, epsilon).seed(seed))
+    }
+
+    /// The level after this one, wrapping around to `Easy`.
+    pub fn next(self) -> Self {
+        match self {
+            Difficulty::Easy => Difficulty::Medium,
+            Difficulty::Medium => Difficulty::Hard,
+            Difficulty::Hard => Difficulty::Perfect,
+            Difficulty::Perfect => Difficulty::Easy,
+        }
+    }
+}
+
+impl fmt::Display for Difficulty {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+/// Strategy from `--ai random|greedy|minimax|perfect|mcts`, `None` without
+/// it.
+///
+/// `--depth N` sets how far minimax looks, `--playouts N` and `--think MS`
+/// budget MCTS, `--mistakes P` plays a random move with probability P and
+/// `--seed N` seeds whatever is random.
+pub fn from_args() -> Option<Box<dyn Strategy>> {
+    parse(std::env::args().skip(1)).unwrap_or_else(|err| {
+        eprintln!("{}", err);
+        None
+    })
+}
+
+pub fn parse<I: IntoIterator<Item = String>>(args: I) -> Result<Option<Box<dyn Strategy>>, String> {
+    const FLAGS: [&str; 6] = [
+        "--ai",
+        "--depth",
+        "--playouts",
+        "--think",
+        "--mistakes",
+        "--seed",
+    ];
+    let mut ai = None;
+    let (mut depth, mut playouts, mut think) = (2, 5000, None);
+    let (mut mistakes, mut seed) = (None, None);
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        if !FLAGS.contains(&arg.as_str()) {
+            continue;
+        }
+        let value = args.next().unwrap_or_default();
+        let number = || {
+            value
+                .parse::<u64>()
+                .map_err(|_| format!("not a number: {}", value))
+        };
+        match arg.as_str() {
+            "--ai" => ai = Some(value.clone()),
+            "--depth" => depth = number()? as usize,
+            "--playouts" => playouts = number()? as usize,
+            "--think" => think = Some(Duration::from_millis(number()?)),
+            "--mistakes" => {
+                let epsilon = value
+                    .parse()
+                    .map_err(|_| format!("not a probability: {}", value))?;
+                mistakes = Some(epsilon);
+            }
+            _ => seed = Some(number()?),
+        }
+    }
+    let seed = seed.unwrap_or_else(rand::random);
+    let strategy: Box<dyn Strategy> = match ai.as_deref() {
+        None => return Ok(None),
+        Some("random") => Box::new(Random::new().seed(seed)),
+        Some("greedy") => Box::new(Greedy::new().seed(seed)),
+        Some("minimax") => Box::new(Minimax { depth }),
+        Some("perfect") => Box::new(Perfect),
+        Some("mcts") => {
+            let mcts = Mcts::new(playouts).seed(seed);
+            Box::new(match think {
+                Some(time) => mcts.time(time),
+                None => mcts,
+            })
+        }
+        Some(ai) => return Err(format!("no such ai: {}", ai)),
+    };
+    Ok(Some(match mistakes {
+        Some(epsilon) => Box::new(Mistakes::new(strategy, epsilon).seed(seed)),
+        None => strategy,
+    }))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::board::BoardState;
+    use crate::brain::check_winner;
+
+    fn args(args: &str) -> Vec<String> {
+        args.split_whitespace().map(String::from).collect()
+    }
+
+    /// Board from rows of `X`, `O` and `.`.
+    fn parse_board(rows: &[&str]) -> Vec<Field> {
+        rows.iter()
+            .flat_map(|row| row.chars())
+            .map(|c| match c {
+                'X' => Field::X,
+                'O' => Field::O,
+                _ => Field::Empty,
+            })
+            .collect()
+    }
+
+    /// Outcome of a game on 3×3, `first` moving first as the human.
+    fn play(first: &mut dyn Strategy, second: &mut dyn Strategy) -> BoardState {
+        let grid = Grid::default();
+        let mut board = vec![Field::Empty; grid.len()];
+        let mut player = Player::Human;
+        while check_winner(&grid, &board) == BoardState::InGame {
+            let cell = match player {
+                Player::Human => first.play(&grid, &board, player),
+                Player::Computer => second.play(&grid, &board, player),
+            };
+            assert_eq!(board[cell], Field::Empty);
+            board[cell] = Field::from(player);
+            player = -player;
+        }
+        check_winner(&grid, &board)
+    }
+
+    #[test]
+    fn greedy_wins_then_blocks() {
+        let grid = Grid::default();
+        let mut greedy = Greedy::new().seed(1);
+        let board = parse_board(&["XX.", "OO.", "..."]);
+        assert_eq!(greedy.play(&grid, &board, Player::Computer), 5);
+        let board = parse_board(&["XX.", "O..", "..."]);
+        assert_eq!(greedy.play(&grid, &board, Player::Computer), 2);
+    }
+
+    #[test]
+    fn minimax_sees_a_fork() {
+        // A corner lets X threaten two lines at once, O has to force play
+        // on an edge instead.
+        let grid = Grid::default();
+        let board = parse_board(&["X..", ".O.", "..X"]);
+        let cell = Minimax { depth: 3 }.play(&grid, &board, Player::Computer);
+        assert!([1, 3, 5, 7].contains(&cell), "{}", cell);
+    }
+
+    #[test]
+    fn mistakes_follow_epsilon() {
+        let grid = Grid::default();
+        let board = parse_board(&["XX.", "O..", "..."]);
+        let blocks = |epsilon: f32| {
+            let mut strategy = Mistakes::new(Box::new(Perfect), epsilon).seed(3);
+            (0..50)
+                .filter(|_| strategy.play(&grid, &board, Player::Computer) == 2)
+                .count()
+        };
+        assert_eq!(blocks(0.), 50);
+        assert!(blocks(1.) < 25);
+    }
+
+    #[test]
+    fn levels_get_harder() {
+        let losses = |difficulty: Difficulty| {
+            let mut strategy = difficulty.strategy();
+            (0..40)
+                .filter(|&game| {
+                    let mut greedy = Greedy::new().seed(game);
+                    let state = play(&mut greedy, strategy.as_mut());
+                    matches!(state, BoardState::Winner(Player::Human, _))
+                })
+                .count()
+        };
+        assert!(losses(Difficulty::Easy) > 0);
+        assert_eq!(losses(Difficulty::Perfect), 0);
+    }
+
+    #[test]
+    fn parses_strategies() {
+        assert!(parse(args("--size 600x600")).unwrap().is_none());
+        let perfect = parse(args("--ai perfect")).unwrap().unwrap();
+        assert_eq!(format!("{:?}", perfect), "Perfect");
+        let mcts = format!(
+            "{:?}",
+            parse(args("--ai mcts --think 200")).unwrap().unwrap()
+        );
+        assert!(mcts.starts_with("Mcts { playouts: 5000, time: Some(200ms)"));
+        let easy = parse(args("--ai greedy --mistakes 0.3")).unwrap().unwrap();
+        assert!(format!("{:?}", easy).starts_with("Mistakes { strategy: Greedy"));
+        assert!(parse(args("--ai alphago")).is_err());
+        assert!(parse(args("--ai mcts --playouts many")).is_err());
+    }
+}
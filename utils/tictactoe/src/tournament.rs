@@ -0,0 +1,507 @@
+//! Strategies playing each other for a number of games, to check that a
+//! faster `minimax` is still perfect. Every pairing plays both colours in
+//! turn, randomness is seeded per game so a run repeats, and an opening book
+//! can start the games off the usual lines.
+use crate::board::BoardState;
+use crate::brain::check_winner;
+use crate::field::Field;
+use crate::grid::Grid;
+use crate::mcts::Mcts;
+use crate::notation::{self, Game};
+use crate::player::Player;
+use crate::strategy::{Difficulty, Greedy, Minimax, Perfect, Random, Strategy};
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// A named player, made anew for every game from that game's seed.
+pub struct Entrant {
+    pub name: String,
+    make: Box<dyn Fn(u64) -> Box<dyn Strategy>>,
+}
+
+impl Entrant {
+    pub fn new<F>(name: &str, make: F) -> Self
+    where
+        F: Fn(u64) -> Box<dyn Strategy> + 'static,
+    {
+        Entrant {
+            name: name.to_string(),
+            make: Box::new(make),
+        }
+    }
+
+    /// `random`, `greedy`, `perfect`, `minimax:DEPTH`, `mcts:PLAYOUTS` or
+    /// one of the levels `easy`, `medium` and `hard`.
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let mut parts = spec.splitn(2, ':');
+        let name = parts.next().unwrap_or_default();
+        let number = match parts.next() {
+            Some(value) => Some(
+                value
+                    .parse::<usize>()
+                    .map_err(|_| format!("not a number: {}", value))?,
+            ),
+            None => None,
+        };
+        let entrant = match (name, number) {
+            ("random", None) => Entrant::new(spec, |seed| Box::new(Random::new().seed(seed))),
+            ("greedy", None) => Entrant::new(spec, |seed| Box::new(Greedy::new().seed(seed))),
+            ("perfect", None) => Entrant::new(spec, |_| Box::new(Perfect)),
+            ("minimax", depth) => {
+                let depth = depth.unwrap_or(2);
+                Entrant::new(spec, move |_| Box::new(Minimax { depth }))
+            }
+            ("mcts", playouts) => {
+                let playouts = playouts.unwrap_or(5000);
+                Entrant::new(spec, move |seed| Box::new(Mcts::new(playouts).seed(seed)))
+            }
+            ("easy", None) => Entrant::new(spec, |seed| Difficulty::Easy.seeded(seed)),
+            ("medium", None) => Entrant::new(spec, |seed| Difficulty::Medium.seeded(seed)),
+            ("hard", None) => Entrant::new(spec, |seed| Difficulty::Hard.seeded(seed)),
+            _ => return Err(format!("no such player: {}", spec)),
+        };
+        Ok(entrant)
+    }
+}
+
+/// Openings for `grid`, one per line in cell names, `#` starts a comment:
+///
+/// ```text
+/// b2 a1
+/// a1 b2 c3
+/// ```
+pub fn parse_book(grid: &Grid, text: &str) -> Result<Vec<Vec<usize>>, String> {
+    text.lines()
+        .map(|line| line.split('#').next().unwrap_or("").trim())
+        .filter(|line| !line.is_empty())
+        .map(|line| {
+            let game = Game::parse(&format!("{}\n{}", notation::header(grid), line))?;
+            Ok(game.moves)
+        })
+        .collect()
+}
+
+pub fn load_book<P: AsRef<Path>>(grid: &Grid, path: P) -> Result<Vec<Vec<usize>>, String> {
+    let text = fs::read_to_string(&path)
+        .map_err(|err| format!("can't read {}: {}", path.as_ref().display(), err))?;
+    parse_book(grid, &text)
+}
+
+/// Games of one side against the other, counted from that side.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Record {
+    pub wins: usize,
+    pub draws: usize,
+    pub losses: usize,
+    pub moves: usize,
+    pub thinking: Duration,
+}
+
+impl Record {
+    pub fn games(&self) -> usize {
+        self.wins + self.draws + self.losses
+    }
+
+    /// Mean time to pick a move, moves from the book don't count.
+    pub fn think_time(&self) -> Duration {
+        if self.moves == 0 {
+            return Duration::default();
+        }
+        self.thinking / self.moves as u32
+    }
+
+    fn add(&mut self, other: &Record) {
+        self.wins += other.wins;
+        self.draws += other.draws;
+        self.losses += other.losses;
+        self.moves += other.moves;
+        self.thinking += other.thinking;
+    }
+
+    /// The same games from the opponent's side, without the thinking.
+    fn reversed(&self) -> Record {
+        Record {
+            wins: self.losses,
+            draws: self.draws,
+            losses: self.wins,
+            ..Record::default()
+        }
+    }
+}
+
+/// Result of a pairing, entrants by their index.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Match {
+    pub first: usize,
+    pub second: usize,
+    /// From the side of `first`.
+    pub first_record: Record,
+    pub second_record: Record,
+}
+
+/// Every entrant against every other, each `games` times.
+pub struct Tournament {
+    pub grid: Grid,
+    pub games: usize,
+    pub seed: u64,
+    pub book: Vec<Vec<usize>>,
+    pub entrants: Vec<Entrant>,
+}
+
+impl Tournament {
+    pub fn new(grid: Grid, games: usize) -> Self {
+        Tournament {
+            grid,
+            games,
+            seed: 0,
+            book: Vec::new(),
+            entrants: Vec::new(),
+        }
+    }
+
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    pub fn book(mut self, book: Vec<Vec<usize>>) -> Self {
+        self.book = book;
+        self
+    }
+
+    pub fn entrant(mut self, entrant: Entrant) -> Self {
+        self.entrants.push(entrant);
+        self
+    }
+
+    /// `--players random,perfect,...` for the entrants, `--games N` per
+    /// pairing, `--seed N`, `--book FILE` and the board from
+    /// `Grid::from_args`. Perfect against greedy without them.
+    pub fn from_args() -> Self {
+        Self::parse(std::env::args().skip(1)).unwrap_or_else(|err| {
+            eprintln!("{}", err);
+            Self::parse(Vec::new()).unwrap()
+        })
+    }
+
+    pub fn parse<I: IntoIterator<Item = String>>(args: I) -> Result<Self, String> {
+        let args: Vec<String> = args.into_iter().collect();
+        let mut tournament = Tournament::new(Grid::parse(args.clone())?, 100);
+        let (mut players, mut book) = ("perfect,greedy".to_string(), None);
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            if !["--players", "--games", "--seed", "--book"].contains(&arg.as_str()) {
+                continue;
+            }
+            let value = args.next().unwrap_or_default();
+            let number = || {
+                value
+                    .parse::<u64>()
+                    .map_err(|_| format!("not a number: {}", value))
+            };
+            match arg.as_str() {
+                "--players" => players = value.clone(),
+                "--games" => tournament.games = number()? as usize,
+                "--seed" => tournament.seed = number()?,
+                _ => book = Some(value.clone()),
+            }
+        }
+        for spec in players.split(',').filter(|spec| !spec.is_empty()) {
+            tournament.entrants.push(Entrant::parse(spec)?);
+        }
+        if let Some(path) = book {
+            tournament.book = load_book(&tournament.grid, path)?;
+        }
+        Ok(tournament)
+    }
+
+    /// Pairs of entrants, a lone entrant plays itself.
+    fn pairings(&self) -> Vec<(usize, usize)> {
+        let count = self.entrants.len();
+        if count == 1 {
+            return vec![(0, 0)];
+        }
+        (0..count)
+            .flat_map(|first| (first + 1..count).map(move |second| (first, second)))
+            .collect()
+    }
+
+    pub fn run(&self) -> Standings {
+        let mut matches = Vec::new();
+        for (pairing, (first, second)) in self.pairings().into_iter().enumerate() {
+            let mut records = [Record::default(), Record::default()];
+            for game in 0..self.games {
+                let seed = self
+                    .seed
+                    .wrapping_mul(1_000_003)
+                    .wrapping_add((pairing * self.games + game) as u64)
+                    .wrapping_mul(2);
+                let mut a = (self.entrants[first].make)(seed);
+                let mut b = (self.entrants[second].make)(seed.wrapping_add(1));
+                let opening: &[usize] = if self.book.is_empty() {
+                    &[]
+                } else {
+                    &self.book[game % self.book.len()]
+                };
+                // Sides swap every game, `x` is the index of whoever is X.
+                let x = game % 2;
+                let (state, mut sides) = if x == 0 {
+                    play(&self.grid, opening, [a.as_mut(), b.as_mut()])
+                } else {
+                    let (state, [x_side, o_side]) =
+                        play(&self.grid, opening, [b.as_mut(), a.as_mut()]);
+                    (state, [o_side, x_side])
+                };
+                let winner = match state {
+                    BoardState::Winner(Player::Human, _) => Some(x),
+                    BoardState::Winner(Player::Computer, _) => Some(1 - x),
+                    _ => None,
+                };
+                for (side, record) in sides.iter_mut().enumerate() {
+                    match winner {
+                        Some(winner) if winner == side => record.wins += 1,
+                        Some(_) => record.losses += 1,
+                        None => record.draws += 1,
+                    }
+                    records[side].add(record);
+                }
+            }
+            let [first_record, second_record] = records;
+            matches.push(Match {
+                first,
+                second,
+                first_record,
+                second_record,
+            });
+        }
+        Standings {
+            names: self.entrants.iter().map(|e| e.name.clone()).collect(),
+            matches,
+        }
+    }
+}
+
+/// Plays out one game from `opening`, X first, timing each side's moves.
+fn play(
+    grid: &Grid,
+    opening: &[usize],
+    strategies: [&mut dyn Strategy; 2],
+) -> (BoardState, [Record; 2]) {
+    let mut records = [Record::default(), Record::default()];
+    let mut board = vec![Field::Empty; grid.len()];
+    let mut moves = opening.iter();
+    let mut player = Player::Human;
+    loop {
+        let state = check_winner(grid, &board);
+        if state != BoardState::InGame {
+            return (state, records);
+        }
+        let side = if player == Player::Human { 0 } else { 1 };
+        let cell = match moves.next() {
+            Some(&cell) => cell,
+            None => {
+                let start = Instant::now();
+                let cell = strategies[side].play(grid, &board, player);
+                records[side].thinking += start.elapsed();
+                records[side].moves += 1;
+                cell
+            }
+        };
+        assert_eq!(
+            board[cell],
+            Field::Empty,
+            "{:?} played a taken cell",
+            player
+        );
+        board[cell] = Field::from(player);
+        player = -player;
+    }
+}
+
+/// What `Tournament::run` found.
+#[derive(Debug, Clone)]
+pub struct Standings {
+    pub names: Vec<String>,
+    pub matches: Vec<Match>,
+}
+
+impl Standings {
+    /// All games of the entrant at `index`, self-play counted once per side.
+    pub fn record(&self, index: usize) -> Record {
+        let mut record = Record::default();
+        for game in &self.matches {
+            if game.first == index {
+                record.add(&game.first_record);
+            }
+            if game.second == index {
+                record.add(&game.second_record);
+            }
+        }
+        record
+    }
+
+    /// The record of `first` against `second`, `None` if they didn't meet.
+    pub fn against(&self, first: usize, second: usize) -> Option<Record> {
+        self.matches.iter().find_map(|game| {
+            if (game.first, game.second) == (first, second) {
+                Some(game.first_record.clone())
+            } else if (game.second, game.first) == (first, second) {
+                Some(game.first_record.reversed())
+            } else {
+                None
+            }
+        })
+    }
+}
+
+impl fmt::Display for Standings {
+    /// A table of wins, draws, losses and think time per entrant, then one
+    /// of W-D-L for the row against the column.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let width = self.names.iter().map(String::len).max().unwrap_or(0).max(6);
+        writeln!(
+            f,
+            "{:width$} {:>6} {:>6} {:>6} {:>12}",
+            "player",
+            "wins",
+            "draws",
+            "losses",
+            "think/move",
+            width = width
+        )?;
+        for (index, name) in self.names.iter().enumerate() {
+            let record = self.record(index);
+            writeln!(
+                f,
+                "{:width$} {:>6} {:>6} {:>6} {:>10.3}ms",
+                name,
+                record.wins,
+                record.draws,
+                record.losses,
+                record.think_time().as_secs_f64() * 1000.,
+                width = width
+            )?;
+        }
+        if self.names.len() < 2 {
+            return Ok(());
+        }
+        writeln!(f)?;
+        write!(f, "{:width$}", "", width = width)?;
+        for name in &self.names {
+            write!(f, " {:>width$}", name, width = width)?;
+        }
+        writeln!(f)?;
+        for (row, name) in self.names.iter().enumerate() {
+            write!(f, "{:width$}", name, width = width)?;
+            for column in 0..self.names.len() {
+                let cell = match self.against(row, column) {
+                    Some(r) if row != column => format!("{}-{}-{}", r.wins, r.draws, r.losses),
+                    _ => "-".to_string(),
+                };
+                write!(f, " {:>width$}", cell, width = width)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn args(args: &str) -> Vec<String> {
+        args.split_whitespace().map(String::from).collect()
+    }
+
+    fn tournament(players: &str, games: usize) -> Tournament {
+        let mut tournament = Tournament::new(Grid::default(), games).seed(7);
+        for spec in players.split(',') {
+            tournament = tournament.entrant(Entrant::parse(spec).unwrap());
+        }
+        tournament
+    }
+
+    #[test]
+    fn perfect_never_loses() {
+        let standings = tournament("perfect,random,greedy,hard", 20).run();
+        let perfect = standings.record(0);
+        assert_eq!(perfect.losses, 0, "\n{}", standings);
+        assert_eq!(perfect.games(), 60);
+        assert!(perfect.wins > 0);
+        assert!(perfect.moves > 0);
+        let mirror = tournament("perfect", 10).run();
+        assert_eq!(mirror.record(0).draws, 20, "\n{}", mirror);
+    }
+
+    #[test]
+    fn seeded_runs_repeat() {
+        let results = |seed: u64| {
+            let standings = tournament("random,greedy,mcts:200", 10).seed(seed).run();
+            (0..3)
+                .map(|index| {
+                    let r = standings.record(index);
+                    (r.wins, r.draws, r.losses)
+                })
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(results(3), results(3));
+    }
+
+    #[test]
+    fn sides_alternate() {
+        // The book hands X a win on its third move, whoever plays X.
+        let book = parse_book(&Grid::default(), "a1 b1 a2 b2 a3").unwrap();
+        let standings = tournament("perfect,random", 6).book(book).run();
+        let record = standings.against(0, 1).unwrap();
+        assert_eq!((record.wins, record.draws, record.losses), (3, 0, 3));
+        assert_eq!(record.moves, 0);
+        assert_eq!(standings.against(1, 0).unwrap().wins, 3);
+    }
+
+    #[test]
+    fn books_and_players_parse() {
+        let grid = Grid::default();
+        let book = parse_book(&grid, "# openings\nb2 a1\n\nc3 # corner\n").unwrap();
+        assert_eq!(book, vec![vec![4, 0], vec![8]]);
+        assert!(parse_book(&grid, "b2 b2").is_err());
+        assert!(parse_book(&grid, "d4").is_err());
+
+        let tournament =
+            Tournament::parse(args("--players perfect,mcts:300,easy --games 4 --seed 9")).unwrap();
+        let names: Vec<&str> = tournament
+            .entrants
+            .iter()
+            .map(|e| e.name.as_str())
+            .collect();
+        assert_eq!(names, ["perfect", "mcts:300", "easy"]);
+        assert_eq!((tournament.games, tournament.seed), (4, 9));
+        assert_eq!(Tournament::parse(Vec::new()).unwrap().entrants.len(), 2);
+        assert!(Tournament::parse(args("--players deep-blue")).is_err());
+        assert!(Tournament::parse(args("--players minimax:far")).is_err());
+        assert!(Tournament::parse(args("--games all")).is_err());
+    }
+
+    #[test]
+    fn standings_show_tables() {
+        let standings = tournament("perfect,random", 4)
+            .book(vec![vec![0, 1, 3, 4, 6]])
+            .run();
+        let text = standings.to_string();
+        let lines: Vec<&str> = text.lines().collect();
+        assert!(
+            lines[0].starts_with("player    wins  draws losses"),
+            "{}",
+            text
+        );
+        assert!(
+            lines[1].starts_with("perfect      2      0      2"),
+            "{}",
+            text
+        );
+        assert!(lines[5].ends_with("2-0-2"), "{}", text);
+        assert!(lines[6].starts_with("random    2-0-2"), "{}", text);
+    }
+}
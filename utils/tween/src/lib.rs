@@ -0,0 +1,162 @@
+//! Easing curves and tweens for animating sketches off `app.time`.
+//!
+//! Times are in seconds, so an animation takes as long at 30 fps as at 144
+//! and a slow frame makes it jump ahead rather than lag behind.
+use std::f32::consts::PI;
+
+/// Shape of the way from 0 to 1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Ease {
+    Linear,
+    InQuad,
+    OutQuad,
+    InOutQuad,
+    InCubic,
+    OutCubic,
+    InOutCubic,
+    InOutSine,
+    /// Overshoots a little before settling.
+    OutBack,
+}
+
+impl Ease {
+    /// Eased `t`, which is clamped to 0..1 first.
+    pub fn apply(self, t: f32) -> f32 {
+        let t = t.clamp(0., 1.);
+        match self {
+            Ease::Linear => t,
+            Ease::InQuad => t * t,
+            Ease::OutQuad => 1. - (1. - t) * (1. - t),
+            Ease::InOutQuad => {
+                if t < 0.5 {
+                    2. * t * t
+                } else {
+                    1. - 2. * (1. - t) * (1. - t)
+                }
+            }
+            Ease::InCubic => t * t * t,
+            Ease::OutCubic => 1. - (1. - t).powi(3),
+            Ease::InOutCubic => {
+                if t < 0.5 {
+                    4. * t * t * t
+                } else {
+                    1. - 4. * (1. - t).powi(3)
+                }
+            }
+            Ease::InOutSine => (1. - (PI * t).cos()) / 2.,
+            Ease::OutBack => {
+                const C: f32 = 1.70158;
+                1. + (C + 1.) * (t - 1.).powi(3) + C * (t - 1.).powi(2)
+            }
+        }
+    }
+}
+
+pub fn lerp(from: f32, to: f32, t: f32) -> f32 {
+    from + (to - from) * t
+}
+
+/// An animation starting at `start` and lasting `duration` seconds.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Tween {
+    pub start: f32,
+    pub duration: f32,
+    pub ease: Ease,
+}
+
+impl Tween {
+    pub fn new(start: f32, duration: f32) -> Self {
+        Tween {
+            start,
+            duration,
+            ease: Ease::Linear,
+        }
+    }
+
+    pub fn ease(mut self, ease: Ease) -> Self {
+        self.ease = ease;
+        self
+    }
+
+    /// Starts `delay` seconds later.
+    pub fn delay(mut self, delay: f32) -> Self {
+        self.start += delay;
+        self
+    }
+
+    pub fn end(&self) -> f32 {
+        self.start + self.duration
+    }
+
+    /// The tween starting when this one ends, for chaining.
+    pub fn then(&self, duration: f32) -> Self {
+        Tween::new(self.end(), duration).ease(self.ease)
+    }
+
+    /// Eased progress at `time`, 0 before the start and 1 from the end on.
+    pub fn progress(&self, time: f32) -> f32 {
+        if self.duration <= 0. {
+            return if time < self.start { 0. } else { 1. };
+        }
+        self.ease.apply((time - self.start) / self.duration)
+    }
+
+    /// Between `from` and `to` at `time`.
+    pub fn value(&self, time: f32, from: f32, to: f32) -> f32 {
+        lerp(from, to, self.progress(time))
+    }
+
+    pub fn started(&self, time: f32) -> bool {
+        time >= self.start
+    }
+
+    pub fn done(&self, time: f32) -> bool {
+        time >= self.end()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const EASES: [Ease; 9] = [
+        Ease::Linear,
+        Ease::InQuad,
+        Ease::OutQuad,
+        Ease::InOutQuad,
+        Ease::InCubic,
+        Ease::OutCubic,
+        Ease::InOutCubic,
+        Ease::InOutSine,
+        Ease::OutBack,
+    ];
+
+    #[test]
+    fn eases_run_from_zero_to_one() {
+        for &ease in EASES.iter() {
+            assert!(ease.apply(0.).abs() < 1e-6, "{:?}", ease);
+            assert!((ease.apply(1.) - 1.).abs() < 1e-6, "{:?}", ease);
+            assert_eq!(ease.apply(-3.), ease.apply(0.));
+            assert_eq!(ease.apply(7.), ease.apply(1.));
+        }
+        assert!(Ease::InQuad.apply(0.5) < 0.5);
+        assert!(Ease::OutQuad.apply(0.5) > 0.5);
+        assert!((Ease::InOutCubic.apply(0.5) - 0.5).abs() < 1e-6);
+        assert!(Ease::OutBack.apply(0.8) > 1.);
+    }
+
+    #[test]
+    fn tweens_follow_time() {
+        let tween = Tween::new(2., 0.5).delay(1.);
+        assert_eq!(tween.progress(0.), 0.);
+        assert!(!tween.started(2.5));
+        assert_eq!(tween.value(3.25, 10., 20.), 15.);
+        assert!(!tween.done(3.4));
+        assert_eq!(tween.value(9., 10., 20.), 20.);
+        let next = tween.ease(Ease::InQuad).then(1.);
+        assert_eq!((next.start, next.ease), (3.5, Ease::InQuad));
+        assert_eq!(next.progress(4.), 0.25);
+        let instant = Tween::new(1., 0.);
+        assert_eq!((instant.progress(0.9), instant.progress(1.)), (0., 1.));
+    }
+}
@@ -0,0 +1,289 @@
+use canvas::Canvas;
+use image::{GrayImage, Luma};
+use nannou::prelude::*;
+use nannou::rand::{rngs::StdRng, Rng, SeedableRng};
+use painter::Painter;
+use std::cell::RefCell;
+
+thread_local! {
+    static RNG: RefCell<Option<StdRng>> = const { RefCell::new(None) };
+}
+
+/// Make everything generated on this thread from now on repeat for the same
+/// `seed`. Without it every brush and texture is different.
+pub fn seed(seed: u64) {
+    RNG.with(|rng| *rng.borrow_mut() = Some(StdRng::seed_from_u64(seed)));
+}
+
+fn random_range(lo: f32, hi: f32) -> f32 {
+    if lo >= hi {
+        return lo;
+    }
+    RNG.with(|rng| match &mut *rng.borrow_mut() {
+        Some(rng) => rng.gen_range(lo, hi),
+        None => nannou::rand::random_range(lo, hi),
+    })
+}
+
+/// Layered-polygon watercolor in the style of Tyler Hobbs.
+///
+/// The base deformation is computed once, every layer is a further deformed
+/// copy of it drawn with a low opacity, so the edges bleed where the layers
+/// disagree.
+pub struct WatercolorBrush {
+    base: Vec<Point2>,
+    stack: Vec<Vec<Point2>>,
+    color: Srgba,
+    opacity: f32,
+    variance: f32,
+    layers: usize,
+    mask: Option<GrayImage>,
+}
+
+impl WatercolorBrush {
+    pub fn new(poly: &[Point2], color: Srgba) -> Self {
+        let variance = radius(poly) / 10.;
+        let mut brush = WatercolorBrush {
+            base: deform(poly, 5, variance, 2.),
+            stack: Vec::new(),
+            color,
+            opacity: 0.04,
+            variance,
+            layers: 30,
+            mask: None,
+        };
+        brush.regenerate();
+        brush
+    }
+
+    /// Number of layers in the stack.
+    pub fn layers(mut self, layers: usize) -> Self {
+        self.layers = layers;
+        self.regenerate();
+        self
+    }
+
+    /// Opacity of a single layer.
+    pub fn opacity(mut self, opacity: f32) -> Self {
+        self.set_opacity(opacity);
+        self
+    }
+
+    /// Change the opacity of a brush in use, the layers stay as they are.
+    pub fn set_opacity(&mut self, opacity: f32) {
+        self.opacity = opacity;
+    }
+
+    /// How far the layers wander from the base shape.
+    pub fn variance(mut self, variance: f32) -> Self {
+        self.variance = variance;
+        self.regenerate();
+        self
+    }
+
+    /// Texture multiplied into every layer, see `texture`.
+    pub fn mask(mut self, mask: GrayImage) -> Self {
+        self.mask = Some(mask);
+        self
+    }
+
+    pub fn color(mut self, color: Srgba) -> Self {
+        self.color = color;
+        self
+    }
+
+    pub fn base(&self) -> &[Point2] {
+        &self.base
+    }
+
+    pub fn stack(&self) -> &[Vec<Point2>] {
+        &self.stack
+    }
+
+    /// Throw away the layers and deform a fresh stack from the same base.
+    pub fn regenerate(&mut self) {
+        let v = self.variance;
+        self.stack = (0..self.layers)
+            .map(|_| {
+                let poly = deform(&self.base, 3, random_range(v * 0.5, v * 2.), 2.);
+                deform(&poly, 3, random_range(v / 1.5, v * 2.), 4.)
+            })
+            .collect();
+    }
+
+    /// Draw the stack with any painter. The mask only applies to `render`.
+    pub fn paint<P: Painter>(&self, painter: &P) {
+        let color = self.layer_color();
+        for poly in &self.stack {
+            painter.polygon(poly, color);
+        }
+    }
+
+    /// Accumulate the stack into `canvas`.
+    pub fn render(&self, canvas: &mut Canvas) {
+        let color = self.layer_color();
+        let (w, h) = (canvas.width(), canvas.height());
+        for poly in &self.stack {
+            let points: Vec<Point2> = poly.iter().map(|&p| canvas.to_pixel(p)).collect();
+            match &self.mask {
+                Some(mask) => {
+                    let dx = random_range(0., mask.width() as f32) as u32;
+                    let dy = random_range(0., mask.height() as f32) as u32;
+                    canvas::raster::fill_polygon(&points, w, h, |x, y, c| {
+                        let (mx, my) = ((x + dx) % mask.width(), (y + dy) % mask.height());
+                        let Luma([v]) = *mask.get_pixel(mx, my);
+                        canvas.blend_pixel(x, y, color, c * v as f32 / 255.);
+                    });
+                }
+                None => canvas.fill_pixels(&points, color),
+            }
+        }
+    }
+
+    fn layer_color(&self) -> Srgba {
+        Srgba::new(
+            self.color.red,
+            self.color.green,
+            self.color.blue,
+            self.color.alpha * self.opacity,
+        )
+    }
+}
+
+/// Random blotches of soft circles, white where paint goes through.
+pub fn texture(width: u32, height: u32, count: usize, max_radius: f32) -> GrayImage {
+    let mut img = GrayImage::new(width, height);
+    for _ in 0..count {
+        let cx = random_range(0., width as f32);
+        let cy = random_range(0., height as f32);
+        let r = random_range(max_radius / 4., max_radius);
+        let (x0, x1) = ((cx - r).max(0.) as u32, (cx + r).min(width as f32) as u32);
+        let (y0, y1) = ((cy - r).max(0.) as u32, (cy + r).min(height as f32) as u32);
+        for y in y0..y1 {
+            for x in x0..x1 {
+                let d = pt2(x as f32 + 0.5, y as f32 + 0.5).distance(pt2(cx, cy));
+                if d < r {
+                    let v = 255. * (1. - d / r);
+                    let Luma([old]) = *img.get_pixel(x, y);
+                    img.put_pixel(x, y, Luma([(old as f32).max(v) as u8]));
+                }
+            }
+        }
+    }
+    img
+}
+
+/// Regular polygon with `n_points` corners.
+pub fn rpoly(radius: f32, n_points: u32) -> Vec<Point2> {
+    (0..n_points)
+        .map(|i| {
+            let fract = i as f32 / n_points as f32;
+            let phase = fract;
+            let x = radius * (TAU * phase).cos();
+            let y = radius * (TAU * phase).sin();
+            pt2(x, y)
+        })
+        .collect()
+}
+
+/// Recursive midpoint displacement of every edge of a closed polygon.
+pub fn deform(points: &[Point2], depth: i64, variance: f32, vdiv: f32) -> Vec<Point2> {
+    let mut new_points = Vec::new();
+
+    for i in 0..points.len() {
+        let sx1 = points[i].x;
+        let sy1 = points[i].y;
+        let sx2 = points[(i + 1) % points.len()].x;
+        let sy2 = points[(i + 1) % points.len()].y;
+
+        new_points.push(pt2(sx1, sy1));
+        sub_divide(&mut new_points, sx1, sy1, sx2, sy2, depth, variance, vdiv);
+    }
+
+    new_points
+}
+
+#[allow(clippy::too_many_arguments)]
+fn sub_divide(
+    new_points: &mut Vec<Point2>,
+    x1: f32,
+    y1: f32,
+    x2: f32,
+    y2: f32,
+    depth: i64,
+    variance: f32,
+    vdiv: f32,
+) {
+    if depth >= 0 {
+        let midx = (x1 + x2) / 2.;
+        let midy = (y1 + y2) / 2.;
+        let nx = midx + random_range(-0.5, 0.5) * variance;
+        let ny = midy + random_range(-0.5, 0.5) * variance;
+
+        sub_divide(new_points, x1, y1, nx, ny, depth - 1, variance / vdiv, vdiv);
+        new_points.push(pt2(nx, ny));
+        sub_divide(new_points, nx, ny, x2, y2, depth - 1, variance / vdiv, vdiv);
+    }
+}
+
+fn radius(poly: &[Point2]) -> f32 {
+    if poly.is_empty() {
+        return 0.;
+    }
+    let center = poly.iter().fold(pt2(0., 0.), |acc, &p| acc + p) / poly.len() as f32;
+    poly.iter().map(|p| p.distance(center)).fold(0., f32::max)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn same_seed_same_shapes() {
+        let shape = |s| {
+            seed(s);
+            deform(&rpoly(50., 4), 2, 10., 2.)
+        };
+        assert_eq!(shape(7), shape(7));
+        assert_ne!(shape(7), shape(8));
+    }
+
+    /// Alpha in the middle of a small canvas after rendering `brush`.
+    fn center_alpha(brush: &WatercolorBrush) -> u8 {
+        let mut canvas = Canvas::new(24, 24);
+        brush.render(&mut canvas);
+        canvas.to_image().get_pixel(12, 12)[3]
+    }
+
+    fn brush(layers: usize) -> WatercolorBrush {
+        seed(3);
+        WatercolorBrush::new(&rpoly(6., 6), rgba(0.5, 0., 0., 1.))
+            .layers(layers)
+            .opacity(0.1)
+    }
+
+    // Layers are deformed six times over, keep the brushes few and small.
+    #[test]
+    fn layers_build_up() {
+        let thin = brush(2);
+        let thin_alpha = center_alpha(&thin);
+        let thick = thin.layers(6);
+        assert_eq!(thick.stack().len(), 6);
+        assert!(thin_alpha > 0);
+        assert!(center_alpha(&thick) > thin_alpha);
+    }
+
+    #[test]
+    fn mask_lets_paint_through_where_white() {
+        let brush = brush(2);
+        let plain = center_alpha(&brush);
+        let brush = brush.mask(GrayImage::from_pixel(8, 8, Luma([255])));
+        assert_eq!(center_alpha(&brush), plain);
+        let brush = brush.mask(GrayImage::new(8, 8));
+        assert_eq!(center_alpha(&brush), 0);
+        let mut brush = brush.mask(GrayImage::from_pixel(8, 8, Luma([128])));
+        assert!((1..plain).contains(&center_alpha(&brush)));
+        brush.set_opacity(0.);
+        assert_eq!(center_alpha(&brush), 0);
+    }
+}